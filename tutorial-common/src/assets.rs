@@ -0,0 +1,40 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+// Environment variable that overrides the directory with the assets of the game.
+pub const ASSETS_DIR_VAR: &str = "TUTORIAL_ASSETS_DIR";
+
+static ASSET_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+// Finds the directory with the assets: it is either the one set by the environment variable, or
+// the directory of the executable if there's `data` folder next to it, or the working directory
+// otherwise. The directory is found once, on first use.
+pub fn asset_root() -> &'static Path {
+    ASSET_ROOT.get_or_init(|| {
+        if let Some(dir) = env::var_os(ASSETS_DIR_VAR) {
+            return PathBuf::from(dir);
+        }
+
+        if let Some(dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        {
+            if dir.join("data").is_dir() {
+                return dir;
+            }
+        }
+
+        env::current_dir().unwrap_or_default()
+    })
+}
+
+// Paths to assets in the tutorial are relative to the assets directory, like
+// `data/models/scene.rgs`. This turns such a path into one that can be loaded no matter where
+// the game was started from. The working directory is left untouched, so paths that are stored
+// inside scenes and models are still resolved against it.
+pub fn asset_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    asset_root().join(path)
+}
@@ -0,0 +1,90 @@
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        Thickness, UiNode, UserInterface,
+    },
+};
+use std::{collections::VecDeque, time::Instant};
+
+// How many last frames are used to calculate the average, min and max frame time.
+const FRAME_WINDOW: usize = 120;
+
+// Debug overlay that shows the frame rate and the frame time in the top left corner.
+pub struct FpsCounter {
+    text: Handle<UiNode>,
+    visible: bool,
+    // Durations of last frames in seconds, the oldest one is at the front.
+    frame_times: VecDeque<f32>,
+    last_frame: Instant,
+}
+
+impl FpsCounter {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                // Widgets on the root canvas must have explicit size, otherwise they won't
+                // be visible.
+                .with_width(400.0)
+                .with_height(60.0)
+                .with_margin(Thickness::uniform(5.0))
+                .with_visibility(false),
+        )
+        .build(&mut ui.build_ctx());
+
+        Self {
+            text,
+            visible: false,
+            frame_times: VecDeque::with_capacity(FRAME_WINDOW),
+            last_frame: Instant::now(),
+        }
+    }
+
+    pub fn toggle(&mut self, ui: &UserInterface) {
+        self.visible = !self.visible;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            self.visible,
+        ));
+    }
+
+    // Must be called once per rendered frame. Game logic runs at fixed rate, so its time step
+    // says nothing about how fast frames are rendered and the real time is measured instead.
+    pub fn register_frame(&mut self) {
+        let now = Instant::now();
+        let frame_time = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
+
+        if self.frame_times.len() == FRAME_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    pub fn update(&self, ui: &UserInterface) {
+        // There's no need to update the text nobody can see.
+        if !self.visible || self.frame_times.is_empty() {
+            return;
+        }
+
+        let average = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
+        let min = self.frame_times.iter().cloned().fold(f32::MAX, f32::min);
+        let max = self.frame_times.iter().cloned().fold(0.0, f32::max);
+
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            format!(
+                "FPS: {:.0}\nFrame time: {:.2} ms (min {:.2} ms, max {:.2} ms)",
+                1.0 / average,
+                average * 1000.0,
+                min * 1000.0,
+                max * 1000.0
+            ),
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    event::{DeviceEvent, ElementState, Event, VirtualKeyCode, WindowEvent},
+    scene::{graph::Graph, node::Node},
+};
+
+#[derive(Default)]
+struct FlyController {
+    move_forward: bool,
+    move_backward: bool,
+    move_left: bool,
+    move_right: bool,
+    move_up: bool,
+    move_down: bool,
+}
+
+// Debug camera that flies around the scene independently of the player: WASD moves it, E and Q
+// move it up and down, the mouse turns it. It takes the camera of the player while it is active
+// and gives the camera back when it is turned off.
+pub struct FreeCamera {
+    // The camera that flies, it is `NONE` while the free camera is off.
+    camera: Handle<Node>,
+    // Parent and local transform of the camera before it was taken, they're restored when the
+    // free camera is turned off.
+    saved_parent: Handle<Node>,
+    saved_position: Vector3<f32>,
+    saved_rotation: UnitQuaternion<f32>,
+    controller: FlyController,
+    // Angles are in degrees, just like the angles of the player.
+    yaw: f32,
+    pitch: f32,
+    // Speed (in meters per second) of the camera.
+    pub speed: f32,
+    // How many degrees the camera turns per pixel of mouse movement.
+    pub mouse_sensitivity: f32,
+}
+
+impl Default for FreeCamera {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            saved_parent: Default::default(),
+            saved_position: Default::default(),
+            saved_rotation: Default::default(),
+            controller: Default::default(),
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 5.0,
+            mouse_sensitivity: 0.5,
+        }
+    }
+}
+
+impl FreeCamera {
+    pub fn is_active(&self) -> bool {
+        self.camera.is_some()
+    }
+
+    // Turns the free camera on and takes the given camera, or turns it off and gives the camera
+    // back.
+    pub fn toggle(&mut self, graph: &mut Graph, camera: Handle<Node>) {
+        if self.is_active() {
+            self.deactivate(graph);
+        } else {
+            self.activate(graph, camera);
+        }
+    }
+
+    fn activate(&mut self, graph: &mut Graph, camera: Handle<Node>) {
+        if !graph.is_valid_handle(camera) {
+            return;
+        }
+
+        let node = &graph[camera];
+        self.saved_parent = node.parent();
+        self.saved_position = **node.local_transform().position();
+        self.saved_rotation = **node.local_transform().rotation();
+
+        // The camera starts flying from where it is and looks where it looked, only its roll
+        // is dropped.
+        let position = node.global_position();
+        let look = node.look_vector();
+        self.yaw = look.x.atan2(look.z).to_degrees();
+        self.pitch = (-look.y).clamp(-1.0, 1.0).asin().to_degrees();
+        self.controller = Default::default();
+
+        // Detach the camera from the player, otherwise it would move along with the player.
+        let root = graph.get_root();
+        graph.link_nodes(camera, root);
+        graph[camera]
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(self.rotation());
+
+        self.camera = camera;
+    }
+
+    fn deactivate(&mut self, graph: &mut Graph) {
+        let camera = std::mem::take(&mut self.camera);
+
+        // The player could be removed while the camera was flying, there's nothing to return
+        // the camera to then.
+        if graph.is_valid_handle(camera) && graph.is_valid_handle(self.saved_parent) {
+            graph.link_nodes(camera, self.saved_parent);
+            graph[camera]
+                .local_transform_mut()
+                .set_position(self.saved_position)
+                .set_rotation(self.saved_rotation);
+        }
+    }
+
+    fn rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.yaw.to_radians())
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.pitch.to_radians())
+    }
+
+    // Input is ignored while the free camera is off.
+    pub fn process_os_event(&mut self, event: &Event<()>) {
+        if !self.is_active() {
+            return;
+        }
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } => {
+                let pressed = input.state == ElementState::Pressed;
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::W) => self.controller.move_forward = pressed,
+                    Some(VirtualKeyCode::S) => self.controller.move_backward = pressed,
+                    Some(VirtualKeyCode::A) => self.controller.move_left = pressed,
+                    Some(VirtualKeyCode::D) => self.controller.move_right = pressed,
+                    Some(VirtualKeyCode::E) => self.controller.move_up = pressed,
+                    Some(VirtualKeyCode::Q) => self.controller.move_down = pressed,
+                    _ => (),
+                }
+            }
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                self.yaw -= self.mouse_sensitivity * delta.0 as f32;
+                self.pitch =
+                    (self.pitch + self.mouse_sensitivity * delta.1 as f32).clamp(-90.0, 90.0);
+            }
+            _ => (),
+        }
+    }
+
+    pub fn update(&self, graph: &mut Graph, dt: f32) {
+        if !graph.is_valid_handle(self.camera) {
+            return;
+        }
+
+        let rotation = self.rotation();
+
+        // The camera moves along its own axes, except up and down movement, which is always
+        // vertical.
+        let mut direction = Vector3::default();
+        if self.controller.move_forward {
+            direction += rotation * Vector3::z();
+        }
+        if self.controller.move_backward {
+            direction -= rotation * Vector3::z();
+        }
+        if self.controller.move_left {
+            direction += rotation * Vector3::x();
+        }
+        if self.controller.move_right {
+            direction -= rotation * Vector3::x();
+        }
+        if self.controller.move_up {
+            direction += Vector3::y();
+        }
+        if self.controller.move_down {
+            direction -= Vector3::y();
+        }
+
+        // Diagonal movement must not be faster than straight one.
+        let velocity = direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+            .scale(self.speed);
+
+        let transform = graph[self.camera].local_transform_mut();
+        let position = **transform.position() + velocity.scale(dt);
+        transform.set_position(position).set_rotation(rotation);
+    }
+}
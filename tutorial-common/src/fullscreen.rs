@@ -0,0 +1,55 @@
+use fyrox::{
+    dpi::PhysicalSize,
+    event::{ElementState, Event, ModifiersState, VirtualKeyCode, WindowEvent},
+    window::{Fullscreen, Window},
+};
+
+// Switches the window between windowed and fullscreen modes by F11 or Alt+Enter.
+#[derive(Default)]
+pub struct FullscreenToggle {
+    // Size of the window before it went fullscreen, it is restored when going back.
+    windowed_size: Option<PhysicalSize<u32>>,
+    modifiers: ModifiersState,
+}
+
+impl FullscreenToggle {
+    pub fn process_os_event(&mut self, event: &Event<()>, window: &Window) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+                WindowEvent::KeyboardInput { input, .. }
+                    if input.state == ElementState::Pressed =>
+                {
+                    let toggle = match input.virtual_keycode {
+                        Some(VirtualKeyCode::F11) => true,
+                        Some(VirtualKeyCode::Return) => self.modifiers.alt(),
+                        _ => false,
+                    };
+
+                    if toggle {
+                        self.toggle(window);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // The window will be resized after switching, the renderer is notified about new size by
+    // the usual `Resized` event.
+    pub fn toggle(&mut self, window: &Window) {
+        if window.fullscreen().is_some() {
+            window.set_fullscreen(None);
+
+            if let Some(size) = self.windowed_size.take() {
+                window.set_inner_size(size);
+            }
+        } else {
+            self.windowed_size = Some(window.inner_size());
+
+            // Borderless fullscreen does not change video mode of the monitor, so switching is
+            // fast and other windows keep working as usual.
+            window.set_fullscreen(Some(Fullscreen::Borderless(window.current_monitor())));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use fyrox::{
+    engine::{resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext},
+    event_loop::{ControlFlow, EventLoop},
+    plugin::PluginConstructor,
+    window::WindowBuilder,
+};
+use std::{
+    env,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+// Environment variable that enables headless mode, its value does not matter.
+pub const HEADLESS_VAR: &str = "TUTORIAL_HEADLESS";
+
+// Rate of the game logic in headless mode, it is the same as the rate of the executor.
+const TIMESTEP: f32 = 1.0 / 60.0;
+
+// Max amount of game ticks per loop iteration. If the game falls behind (for example the
+// process was suspended for a while), the time beyond the limit is dropped instead of being
+// caught up all at once.
+const MAX_SUBSTEPS: u32 = 5;
+
+// The game runs headless when it is started with `--headless` argument or when the environment
+// variable is set.
+pub fn is_headless() -> bool {
+    env::args().skip(1).any(|arg| arg == "--headless") || env::var_os(HEADLESS_VAR).is_some()
+}
+
+// The executor always creates a window, so the headless game creates the engine itself and runs
+// its own loop. The loop updates plugins and scripts at the usual fixed rate and never asks the
+// engine to render a frame. Nobody sends OS events to a game without a window, so `on_os_event`
+// of plugins and scripts is never called.
+pub fn run_headless<P>(title: &str, plugin_constructor: P)
+where
+    P: PluginConstructor + 'static,
+{
+    // The engine needs an event loop to be created, but the loop is never run.
+    let event_loop = EventLoop::new();
+    let serialization_context = Arc::new(SerializationContext::new());
+    let mut engine = Engine::new(EngineInitParams {
+        window_builder: WindowBuilder::new().with_title(title),
+        resource_manager: ResourceManager::new(serialization_context.clone()),
+        serialization_context,
+        events_loop: &event_loop,
+        vsync: false,
+        // Keeps the window hidden. The engine still creates its graphics context and renderer,
+        // they're just never used.
+        headless: true,
+    })
+    .unwrap();
+
+    engine.add_plugin_constructor(plugin_constructor);
+    engine.enable_plugins(Default::default(), true);
+
+    let mut control_flow = ControlFlow::Poll;
+    let mut previous = Instant::now();
+    let mut lag = 0.0;
+
+    // Plugins could request exit, just like in the executor.
+    while control_flow != ControlFlow::Exit {
+        lag += previous.elapsed().as_secs_f32();
+        previous = Instant::now();
+        lag = lag.min(MAX_SUBSTEPS as f32 * TIMESTEP);
+
+        while lag >= TIMESTEP {
+            lag -= TIMESTEP;
+
+            engine.update(TIMESTEP, &mut control_flow, &mut lag, Default::default());
+        }
+
+        // There's nothing to render, so just wait for the next tick.
+        thread::sleep(Duration::from_secs_f32((TIMESTEP - lag).max(0.0)));
+    }
+}
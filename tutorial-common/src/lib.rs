@@ -0,0 +1,9 @@
+// Code that every tutorial uses the same way: finding assets, loading the skybox and a few
+// debug helpers. Each tutorial depends on this crate instead of keeping its own copy.
+
+pub mod assets;
+pub mod fps;
+pub mod free_camera;
+pub mod fullscreen;
+pub mod headless;
+pub mod skybox;
@@ -0,0 +1,56 @@
+use crate::assets::asset_path;
+use fyrox::{
+    core::futures::future::join_all,
+    engine::resource_manager::ResourceManager,
+    resource::texture::TextureWrapMode,
+    scene::camera::{SkyBox, SkyBoxBuilder},
+};
+
+// Loads a skybox from the textures in `base_path` directory (relative to the assets directory).
+// There must be six textures, one per side of the skybox, they're named after the sides: front,
+// back, left, right, up and down (all in jpg format).
+pub async fn load_skybox(
+    resource_manager: ResourceManager,
+    base_path: &str,
+) -> Result<SkyBox, String> {
+    let sides = ["front", "back", "left", "right", "up", "down"];
+
+    // Load skybox textures in parallel.
+    let results = join_all(sides.iter().map(|side| {
+        resource_manager.request_texture(asset_path(base_path).join(format!("{}.jpg", side)))
+    }))
+    .await;
+
+    let mut textures = Vec::new();
+    for (side, result) in sides.iter().zip(results) {
+        textures.push(result.map_err(|err| {
+            format!(
+                "Unable to load {} side of the skybox from {}! Reason: {:?}",
+                side, base_path, err
+            )
+        })?);
+    }
+
+    // Textures are in the same order as the sides.
+    let mut textures = textures.into_iter();
+    let skybox = SkyBoxBuilder {
+        front: textures.next(),
+        back: textures.next(),
+        left: textures.next(),
+        right: textures.next(),
+        top: textures.next(),
+        bottom: textures.next(),
+    }
+    .build()
+    .map_err(|err| format!("Unable to create the skybox! Reason: {:?}", err))?;
+
+    // Set S and T coordinate wrap mode, ClampToEdge will remove any possible seams on edges
+    // of the skybox.
+    if let Some(skybox_texture) = skybox.cubemap() {
+        let mut data = skybox_texture.data_ref();
+        data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    }
+
+    Ok(skybox)
+}
@@ -0,0 +1,17 @@
+//! Editor with the game connected to it as a plugin.
+use fyrox::event_loop::EventLoop;
+use fyroxed_base::{Editor, StartupData};
+use tutorial_character_controller::GameConstructor;
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let mut editor = Editor::new(
+        &event_loop,
+        Some(StartupData {
+            working_directory: Default::default(),
+            scene: "data/models/scene.rgs".into(),
+        }),
+    );
+    editor.add_game_plugin(GameConstructor);
+    editor.run(event_loop)
+}
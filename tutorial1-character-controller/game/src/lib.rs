@@ -0,0 +1,248 @@
+use crate::player::Player;
+use fyrox::{
+    core::{algebra::Vector3, futures::executor::block_on, pool::Handle},
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    plugin::{Plugin, PluginConstructor, PluginContext, PluginRegistrationContext},
+    scene::{
+        base::BaseBuilder,
+        camera::{CameraBuilder, SkyBox},
+        collider::{ColliderBuilder, ColliderShape},
+        loader::AsyncSceneLoader,
+        node::Node,
+        rigidbody::RigidBodyBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+    utils::log::Log,
+};
+use tutorial_common::{
+    assets::asset_path, fps::FpsCounter, free_camera::FreeCamera, fullscreen::FullscreenToggle,
+    skybox::load_skybox,
+};
+
+pub mod player;
+
+// Checks whether the game is paused, scripts must do nothing while it is.
+pub fn is_game_paused(plugins: &[Box<dyn Plugin>]) -> bool {
+    plugins
+        .iter()
+        .filter_map(|plugin| plugin.cast::<Game>())
+        .any(|game| game.paused)
+}
+
+// Checks whether the debug free camera is flying, the player must not touch the camera and must
+// not move while it is.
+pub fn is_free_camera_active(plugins: &[Box<dyn Plugin>]) -> bool {
+    plugins
+        .iter()
+        .filter_map(|plugin| plugin.cast::<Game>())
+        .any(|game| game.free_camera.is_active())
+}
+
+pub struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn register(&self, context: PluginRegistrationContext) {
+        // Register scripts, so they could be saved with the scene and assigned in the editor.
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Player>("Player");
+    }
+
+    fn create_instance(
+        &self,
+        override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        Box::new(Game::new(override_scene, context))
+    }
+}
+
+fn create_player(scene: &mut Scene, skybox: Option<SkyBox>) -> Handle<Node> {
+    // Create rigid body with a camera, move it a bit up to "emulate" head.
+    let camera = CameraBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 0.25, 0.0))
+                .build(),
+        ),
+    )
+    .build(&mut scene.graph);
+    scene.graph[camera].as_camera_mut().set_skybox(skybox);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    // Offset player a bit.
+                    .with_local_position(Vector3::new(0.0, 1.0, -1.0))
+                    .build(),
+            )
+            .with_children(&[
+                camera,
+                // Add capsule collider for the rigid body.
+                ColliderBuilder::new(BaseBuilder::new())
+                    .with_shape(ColliderShape::capsule_y(0.25, 0.2))
+                    .build(&mut scene.graph),
+            ])
+            // The script will drive the body using the input from the OS.
+            .with_script(Script::new(Player {
+                camera,
+                ..Default::default()
+            })),
+    )
+    // We don't want the player to tilt.
+    .with_locked_rotations(true)
+    // We don't want the rigid body to sleep (be excluded from simulation)
+    .with_can_sleep(false)
+    .build(&mut scene.graph)
+}
+
+// Adds the player to the scene, unless there's one already (it could be placed in the editor).
+fn add_player(scene: &mut Scene, skybox: Option<SkyBox>) {
+    let has_player = scene
+        .graph
+        .linear_iter()
+        .any(|node| node.try_get_script::<Player>().is_some());
+
+    if !has_player {
+        create_player(scene, skybox);
+    }
+}
+
+fn player_camera(scene: &Scene) -> Handle<Node> {
+    scene
+        .graph
+        .linear_iter()
+        .find_map(|node| node.try_get_script::<Player>())
+        .map_or(Handle::NONE, |player| player.camera)
+}
+
+pub struct Game {
+    scene: Handle<Scene>,
+    loader: Option<AsyncSceneLoader>,
+    // The skybox is loaded once on start and given to the player's camera when the scene is ready.
+    skybox: Option<SkyBox>,
+    paused: bool,
+    fps_counter: FpsCounter,
+    fullscreen: FullscreenToggle,
+    // Debug camera, it keeps the transform the player's camera had before it was taken.
+    free_camera: FreeCamera,
+}
+
+impl Game {
+    pub fn new(override_scene: Handle<Scene>, context: PluginContext) -> Self {
+        let mut loader = None;
+        let scene = if override_scene.is_some() {
+            // The editor passes its own scene when the game is started from it.
+            override_scene
+        } else {
+            // Load the scene in the background, it will be added to the engine once loaded.
+            loader = Some(AsyncSceneLoader::begin_loading(
+                asset_path("data/models/scene.rgs"),
+                context.serialization_context.clone(),
+                context.resource_manager.clone(),
+            ));
+            Default::default()
+        };
+
+        // The camera works fine without a skybox, so the game can go on if it can't be loaded.
+        let skybox = block_on(load_skybox(
+            context.resource_manager.clone(),
+            "data/textures/skybox",
+        ))
+        .map_err(Log::err)
+        .ok();
+
+        // The scene from the editor is ready right away, the loaded one gets its player once it
+        // is loaded.
+        if let Some(scene) = context.scenes.try_get_mut(scene) {
+            add_player(scene, skybox.clone());
+        }
+
+        Self {
+            scene,
+            loader,
+            skybox,
+            paused: false,
+            fps_counter: FpsCounter::new(context.user_interface),
+            fullscreen: Default::default(),
+            free_camera: Default::default(),
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        self.fps_counter.update(context.user_interface);
+
+        if let Some(loader) = self.loader.as_ref() {
+            if let Some(result) = loader.fetch_result() {
+                match result {
+                    Ok(mut scene) => {
+                        add_player(&mut scene, self.skybox.clone());
+
+                        self.scene = context.scenes.add(scene);
+                    }
+                    Err(err) => Log::err(format!("Unable to load scene! Reason: {:?}", err)),
+                }
+
+                self.loader = None;
+            }
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.free_camera.update(&mut scene.graph, context.dt);
+        }
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        context: PluginContext,
+        control_flow: &mut ControlFlow,
+    ) {
+        self.fullscreen.process_os_event(event, context.window);
+        self.free_camera.process_os_event(event);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if input.state == ElementState::Pressed => match input.virtual_keycode {
+                // Pause or unpause the game by hitting Escape. This event is handled even when
+                // the game is paused, otherwise there would be no way to unpause it.
+                Some(VirtualKeyCode::Escape) => {
+                    self.paused = !self.paused;
+
+                    // Physics must be frozen too, the scene is still rendered as usual.
+                    if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                        *scene.graph.physics.enabled = !self.paused;
+                    }
+                }
+                // Quit the game by hitting Q while it is paused, so a stray key press while
+                // playing won't close it. The free camera flies down with Q, so it must be
+                // returned to the player first.
+                Some(VirtualKeyCode::Q) if self.paused && !self.free_camera.is_active() => {
+                    *control_flow = ControlFlow::Exit
+                }
+                // Show or hide frame rate counter by hitting F3.
+                Some(VirtualKeyCode::F3) => self.fps_counter.toggle(context.user_interface),
+                // Let the camera fly around the scene by hitting F4, hitting it again returns
+                // the camera to the player.
+                Some(VirtualKeyCode::F4) => {
+                    if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                        let camera = player_camera(scene);
+                        self.free_camera.toggle(&mut scene.graph, camera);
+                    }
+                }
+                _ => (),
+            },
+            Event::RedrawRequested(_) => self.fps_counter.register_frame(),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{is_free_camera_active, is_game_paused};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    event::{DeviceEvent, ElementState, Event, VirtualKeyCode, WindowEvent},
+    impl_component_provider,
+    scene::node::{Node, TypeUuidProvider},
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Default, Debug, Clone)]
+struct InputController {
+    move_forward: bool,
+    move_backward: bool,
+    move_left: bool,
+    move_right: bool,
+    pitch: f32,
+    yaw: f32,
+}
+
+// The script must be assigned to a rigid body node, its camera is a child of the body.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Player {
+    pub camera: Handle<Node>,
+    // How many degrees the camera turns per pixel of mouse movement.
+    pub mouse_sensitivity: f32,
+    // Whether moving the mouse up makes the camera look down.
+    pub invert_y: bool,
+
+    // Input state is not needed in saved games and in the editor.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    controller: InputController,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            mouse_sensitivity: 1.0,
+            invert_y: false,
+            controller: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(Player);
+
+impl TypeUuidProvider for Player {
+    fn type_uuid() -> Uuid {
+        uuid!("7b2b7b34-9f39-4e4d-a8c1-3f2d6c6b1e0a")
+    }
+}
+
+impl ScriptTrait for Player {
+    fn on_os_event(&mut self, event: &Event<()>, context: &mut ScriptContext) {
+        match event {
+            Event::WindowEvent { event, .. } => {
+                if let WindowEvent::KeyboardInput { input, .. } = event {
+                    if let Some(key_code) = input.virtual_keycode {
+                        match key_code {
+                            VirtualKeyCode::W => {
+                                self.controller.move_forward = input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::S => {
+                                self.controller.move_backward =
+                                    input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::A => {
+                                self.controller.move_left = input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::D => {
+                                self.controller.move_right = input.state == ElementState::Pressed;
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            // Keys are still tracked while paused or while the free camera flies, so releasing
+            // a key meanwhile won't leave the player walking, but the camera must stay where
+            // it is.
+            Event::DeviceEvent { event, .. }
+                if !is_game_paused(context.plugins) && !is_free_camera_active(context.plugins) =>
+            {
+                if let DeviceEvent::MouseMotion { delta } = event {
+                    let y_sign = if self.invert_y { -1.0 } else { 1.0 };
+
+                    self.controller.yaw -= self.mouse_sensitivity * delta.0 as f32;
+
+                    self.controller.pitch = (self.controller.pitch
+                        + y_sign * self.mouse_sensitivity * delta.1 as f32)
+                        .clamp(-90.0, 90.0);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if is_game_paused(context.plugins) {
+            return;
+        }
+
+        let scene = &mut context.scene;
+
+        // The camera is taken by the free camera, the player just stands still until it is
+        // returned.
+        if is_free_camera_active(context.plugins) {
+            let body = scene.graph[context.handle].as_rigid_body_mut();
+            body.set_lin_vel(Vector3::new(0.0, body.lin_vel().y, 0.0));
+            return;
+        }
+
+        // Set pitch for the camera. These lines responsible for up-down camera rotation.
+        scene.graph[self.camera].local_transform_mut().set_rotation(
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.controller.pitch.to_radians()),
+        );
+
+        // Borrow rigid body node, the script is assigned to it.
+        let body = scene.graph[context.handle].as_rigid_body_mut();
+
+        // Keep only vertical velocity, and drop horizontal.
+        let mut velocity = Vector3::new(0.0, body.lin_vel().y, 0.0);
+
+        // Change the velocity depending on the keys pressed.
+        if self.controller.move_forward {
+            // If we moving forward then add "look" vector of the body.
+            velocity += body.look_vector();
+        }
+        if self.controller.move_backward {
+            // If we moving backward then subtract "look" vector of the body.
+            velocity -= body.look_vector();
+        }
+        if self.controller.move_left {
+            // If we moving left then add "side" vector of the body.
+            velocity += body.side_vector();
+        }
+        if self.controller.move_right {
+            // If we moving right then subtract "side" vector of the body.
+            velocity -= body.side_vector();
+        }
+
+        // Finally new linear velocity.
+        body.set_lin_vel(velocity);
+
+        // Change the rotation of the rigid body according to current yaw. These lines responsible for
+        // left-right rotation.
+        body.local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                self.controller.yaw.to_radians(),
+            ));
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
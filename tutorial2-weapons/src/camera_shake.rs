@@ -0,0 +1,83 @@
+use fyrox::core::algebra::{UnitQuaternion, Vector3};
+
+// Shakes the camera on shots, explosions, etc. Every event adds some "trauma", which slowly
+// decays over time, and the camera is shaken while there's any trauma left. Strength of the
+// shake is proportional to the square of trauma, so small amounts of trauma are barely
+// noticeable, while big ones shake the camera a lot.
+pub struct CameraShake {
+    // Max offset (in meters) of the camera from its base position.
+    pub max_offset: f32,
+    // Max angle (in degrees) by which the camera is rotated around each axis.
+    pub max_angle: f32,
+    // How much trauma is removed every second.
+    pub decay_rate: f32,
+    // How fast the camera shakes, the higher the value, the more "violent" the shake is.
+    pub frequency: f32,
+    // Current amount of trauma in [0; 1] range.
+    trauma: f32,
+    // Time (in seconds) used to sample the noise.
+    time: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            max_offset: 0.02,
+            max_angle: 2.0,
+            decay_rate: 1.5,
+            frequency: 25.0,
+            trauma: 0.0,
+            time: 0.0,
+        }
+    }
+}
+
+// Cheap smooth noise in [-1; 1] range, it is a sum of a few sine waves with "random"
+// frequencies. Each axis uses its own `seed`, so the axes move independently.
+fn noise(seed: f32, t: f32) -> f32 {
+    ((t + seed).sin() + 0.5 * (2.3 * t + 1.7 * seed).sin() + 0.25 * (4.7 * t + 3.1 * seed).sin())
+        / 1.75
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.trauma = (self.trauma - self.decay_rate * dt).max(0.0);
+        self.time += dt;
+    }
+
+    fn strength(&self) -> f32 {
+        self.trauma * self.trauma
+    }
+
+    // Offset that must be added to the base position of the camera. It is exactly zero when
+    // there's no trauma, so the camera returns to its base position once the shake is over.
+    pub fn offset(&self) -> Vector3<f32> {
+        if self.trauma <= 0.0 {
+            return Vector3::default();
+        }
+
+        let t = self.time * self.frequency;
+        Vector3::new(noise(1.0, t), noise(2.0, t), noise(3.0, t))
+            .scale(self.max_offset * self.strength())
+    }
+
+    // Rotation that must be applied on top of the base rotation of the camera, it is identity
+    // when there's no trauma.
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        if self.trauma <= 0.0 {
+            return UnitQuaternion::identity();
+        }
+
+        let t = self.time * self.frequency;
+        let angle = self.max_angle.to_radians() * self.strength();
+        UnitQuaternion::from_euler_angles(
+            angle * noise(4.0, t),
+            angle * noise(5.0, t),
+            angle * noise(6.0, t),
+        )
+    }
+}
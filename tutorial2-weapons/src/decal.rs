@@ -0,0 +1,147 @@
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        math::vector_to_quat,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    material::{Material, PropertyValue, SharedMaterial},
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+    },
+    utils::log::Log,
+};
+use std::collections::VecDeque;
+
+// Time (in seconds) at the end of the lifetime of a decal, during which the decal fades out.
+const FADE_TIME: f32 = 1.0;
+
+const COLOR: Color = Color::opaque(20, 20, 20);
+
+struct Decal {
+    node: Handle<Node>,
+    // Every decal has its own material, so decals could fade out independently.
+    material: SharedMaterial,
+    age: f32,
+}
+
+// Bullet holes that are left on static geometry. Holes stay for some time and then fade out,
+// there could be only limited amount of them, so when there are too many holes, the oldest one
+// is moved to the place of the new one.
+pub struct BulletDecals {
+    // Time (in seconds) a decal stays on a surface, including the time it fades out.
+    pub lifetime: f32,
+    // Max amount of decals at the same time.
+    pub max_count: usize,
+    // The oldest decal is at the front.
+    decals: VecDeque<Decal>,
+}
+
+fn set_color(material: &SharedMaterial, color: Color) {
+    Log::verify(material.lock().set_property(
+        &ImmutableString::new("diffuseColor"),
+        PropertyValue::Color(color),
+    ));
+}
+
+impl BulletDecals {
+    pub fn new(max_count: usize, lifetime: f32) -> Self {
+        Self {
+            lifetime,
+            max_count,
+            decals: Default::default(),
+        }
+    }
+
+    fn create_decal(graph: &mut Graph) -> Decal {
+        // A hole is a thin box, unlike a flat quad it is visible from any side.
+        let shape = SurfaceSharedData::new(SurfaceData::make_cube(
+            Matrix4::new_nonuniform_scaling(&Vector3::new(0.02, 0.02, 0.001)),
+        ));
+
+        let mut material = Material::standard();
+        material
+            .set_property(
+                &ImmutableString::new("diffuseColor"),
+                PropertyValue::Color(COLOR),
+            )
+            .unwrap();
+        let material = SharedMaterial::new(material);
+
+        let node = MeshBuilder::new(BaseBuilder::new().with_cast_shadows(false))
+            .with_surfaces(vec![SurfaceBuilder::new(shape)
+                .with_material(material.clone())
+                .build()])
+            // Forward render path is needed to make the decal transparent when it fades out.
+            .with_render_path(RenderPath::Forward)
+            .build(graph);
+
+        Decal {
+            node,
+            material,
+            age: 0.0,
+        }
+    }
+
+    // Leaves a hole at the given point of a surface with the given normal.
+    pub fn add(&mut self, graph: &mut Graph, position: Vector3<f32>, normal: Vector3<f32>) {
+        if self.max_count == 0 {
+            return;
+        }
+
+        // Remove extra decals, if the max count was decreased.
+        while self.decals.len() > self.max_count {
+            if let Some(decal) = self.decals.pop_front() {
+                graph.remove_node(decal.node);
+            }
+        }
+
+        let mut decal = if self.decals.len() == self.max_count {
+            self.decals.pop_front().unwrap()
+        } else {
+            Self::create_decal(graph)
+        };
+
+        decal.age = 0.0;
+        set_color(&decal.material, COLOR);
+
+        // Move the decal a bit off the surface, otherwise it would flicker.
+        graph[decal.node]
+            .local_transform_mut()
+            .set_position(position + normal.scale(0.001))
+            .set_rotation(vector_to_quat(normal));
+
+        self.decals.push_back(decal);
+    }
+
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) {
+        for decal in self.decals.iter_mut() {
+            decal.age += dt;
+
+            let time_left = self.lifetime - decal.age;
+            if time_left < FADE_TIME {
+                let alpha = (time_left / FADE_TIME).max(0.0);
+                set_color(
+                    &decal.material,
+                    Color::from_rgba(COLOR.r, COLOR.g, COLOR.b, (alpha * 255.0) as u8),
+                );
+            }
+        }
+
+        // Decals are ordered by age, so the expired ones are at the front.
+        while let Some(decal) = self.decals.front() {
+            if decal.age < self.lifetime {
+                break;
+            }
+            graph.remove_node(decal.node);
+            self.decals.pop_front();
+        }
+    }
+}
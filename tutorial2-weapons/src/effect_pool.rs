@@ -0,0 +1,77 @@
+use fyrox::{
+    core::pool::Handle,
+    scene::{graph::Graph, node::Node},
+};
+
+// A set of reusable effect nodes of the same kind (bullet impacts, shot trails, etc). Effects
+// are created very often during rapid fire, and instead of creating a new node for every
+// effect and removing it shortly after, the pool hides finished effects and reuses them later.
+pub struct EffectPool {
+    // Hidden nodes that are ready to be reused.
+    free: Vec<Handle<Node>>,
+    // Nodes that are shown right now, along with the time (in seconds) they have left.
+    active: Vec<(Handle<Node>, f32)>,
+    // Max amount of nodes the pool owns.
+    max_size: usize,
+    // How long (in seconds) an effect is shown.
+    lifetime: f32,
+}
+
+impl EffectPool {
+    pub fn new(max_size: usize, lifetime: f32) -> Self {
+        Self {
+            free: Default::default(),
+            active: Default::default(),
+            max_size,
+            lifetime,
+        }
+    }
+
+    // Returns a node for a new effect, the node is either a free one from the pool, or a new
+    // one made by `create`. Reused nodes keep their state, so the caller must move them to the
+    // new place and restart them.
+    pub fn acquire<F>(&mut self, graph: &mut Graph, create: F) -> Handle<Node>
+    where
+        F: FnOnce(&mut Graph) -> Handle<Node>,
+    {
+        if let Some(node) = self.free.pop() {
+            graph[node].set_visibility(true);
+            self.active.push((node, self.lifetime));
+            node
+        } else if self.active.len() < self.max_size {
+            let node = create(graph);
+            self.active.push((node, self.lifetime));
+            node
+        } else {
+            // The pool is exhausted, so make a temporary node, the engine will remove it
+            // automatically when its lifetime is over.
+            let node = create(graph);
+            graph[node].set_lifetime(Some(self.lifetime));
+            node
+        }
+    }
+
+    // Hides the node and puts it back to the pool.
+    pub fn release(&mut self, graph: &mut Graph, node: Handle<Node>) {
+        if let Some(index) = self.active.iter().position(|(handle, _)| *handle == node) {
+            self.active.remove(index);
+            graph[node].set_visibility(false);
+            self.free.push(node);
+        }
+    }
+
+    // Releases every effect that was shown long enough.
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) {
+        let mut finished = Vec::new();
+        for (node, time_left) in self.active.iter_mut() {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                finished.push(*node);
+            }
+        }
+
+        for node in finished {
+            self.release(graph, node);
+        }
+    }
+}
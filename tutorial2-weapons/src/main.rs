@@ -1,24 +1,54 @@
-use crate::{message::Message, weapon::Weapon};
+use crate::{
+    camera_shake::CameraShake,
+    decal::BulletDecals,
+    effect_pool::EffectPool,
+    message::Message,
+    pickup::{
+        create_ammo_pickup, create_health_pickup, create_weapon_pickup, find_touched_pickups,
+        AmmoPickup, HealthPickup, PickupItem, WeaponPickup,
+    },
+    projectile::Projectile,
+    quit_dialog::{QuitAnswer, QuitDialog},
+    surface::SurfaceKind,
+    weapon::{create_dry_fire_sound_buffer, create_weapon, load_weapon_model, Weapon, WeaponKind},
+};
 use fyrox::{
     core::{
         algebra::{Point3, UnitQuaternion, Vector3},
         color::Color,
         color_gradient::{ColorGradient, GradientPoint},
+        futures::future::join_all,
         math::ray::Ray,
         math::vector_to_quat,
         pool::{Handle, Pool},
         sstorage::ImmutableString,
     },
     engine::{resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext},
-    event::{DeviceEvent, ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent},
+    event::{
+        DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode,
+        WindowEvent,
+    },
     event_loop::{ControlFlow, EventLoop},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+    },
     material::{Material, PropertyValue, SharedMaterial},
-    resource::texture::TextureWrapMode,
+    rand::Rng,
+    resource::model::Model,
     scene::{
         base::BaseBuilder,
-        camera::{CameraBuilder, SkyBox, SkyBoxBuilder},
+        camera::CameraBuilder,
         collider::{ColliderBuilder, ColliderShape},
-        graph::{physics::RayCastOptions, Graph},
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
         mesh::{
             surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
             MeshBuilder, RenderPath,
@@ -29,27 +59,62 @@ use fyrox::{
             ParticleSystemBuilder,
         },
         pivot::PivotBuilder,
-        rigidbody::RigidBodyBuilder,
+        rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+        sound::{SoundBufferResource, SoundBuilder, Status},
         transform::TransformBuilder,
         Scene,
     },
+    utils::{log::Log, translate_event},
     window::WindowBuilder,
 };
 use std::{
-    path::Path,
+    collections::HashMap,
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc,
     },
-    time,
+    thread, time,
+};
+use tutorial_common::{
+    assets::asset_path, fps::FpsCounter, free_camera::FreeCamera, fullscreen::FullscreenToggle,
+    headless::is_headless, skybox::load_skybox,
 };
 
+pub mod camera_shake;
+pub mod decal;
+pub mod effect_pool;
 pub mod message;
+pub mod pickup;
+pub mod projectile;
+pub mod quit_dialog;
+pub mod surface;
 pub mod weapon;
 
 // Our game logic will be updated at 60 Hz rate.
 const TIMESTEP: f32 = 1.0 / 60.0;
 
+// Max amount of game ticks per frame, see the main loop for details.
+const MAX_SUBSTEPS: u32 = 5;
+
+// Size of the HUD in headless mode, there's no window to take the size from. Nothing is drawn,
+// so any size works.
+const HEADLESS_FRAME_SIZE: (u32, u32) = (1280, 720);
+
+// Height of the camera above the center of the player's rigid body, it "emulates" head.
+const CAMERA_HEIGHT: f32 = 0.25;
+
+// Impacts closer than this distance (in meters) to the player shake the camera.
+const IMPACT_SHAKE_RADIUS: f32 = 10.0;
+
+// Order of the weapons in the inventory. The player starts with the first one, the rest must be
+// picked up.
+const WEAPONS: [WeaponKind; 4] = [
+    WeaponKind::Pistol,
+    WeaponKind::Rifle,
+    WeaponKind::Shotgun,
+    WeaponKind::GrenadeLauncher,
+];
+
 #[derive(Default)]
 struct InputController {
     move_forward: bool,
@@ -59,68 +124,95 @@ struct InputController {
     pitch: f32,
     yaw: f32,
     shoot: bool,
+    trigger_pulled: bool,
+}
+
+// Keys and buttons that are used to control the player, they can be changed at any time.
+struct KeyBindings {
+    move_forward: VirtualKeyCode,
+    move_backward: VirtualKeyCode,
+    move_left: VirtualKeyCode,
+    move_right: VirtualKeyCode,
+    shoot: MouseButton,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_forward: VirtualKeyCode::W,
+            move_backward: VirtualKeyCode::S,
+            move_left: VirtualKeyCode::A,
+            move_right: VirtualKeyCode::D,
+            shoot: MouseButton::Left,
+        }
+    }
+}
+
+impl KeyBindings {
+    // Switches movement between WASD and arrow keys, it is an example of how the bindings
+    // could be changed at runtime.
+    fn toggle_arrow_keys(&mut self) {
+        if self.move_forward == VirtualKeyCode::Up {
+            let default = Self::default();
+            self.move_forward = default.move_forward;
+            self.move_backward = default.move_backward;
+            self.move_left = default.move_left;
+            self.move_right = default.move_right;
+        } else {
+            self.move_forward = VirtualKeyCode::Up;
+            self.move_backward = VirtualKeyCode::Down;
+            self.move_left = VirtualKeyCode::Left;
+            self.move_right = VirtualKeyCode::Right;
+        }
+    }
 }
 
 struct Player {
     camera: Handle<Node>,
     rigid_body: Handle<Node>,
     controller: InputController,
+    key_bindings: KeyBindings,
+    // How many degrees the camera turns per pixel of mouse movement.
+    mouse_sensitivity: f32,
+    // Whether moving the mouse up makes the camera look down.
+    invert_y: bool,
     weapon_pivot: Handle<Node>,
     sender: Sender<Message>,
-    weapon: Handle<Weapon>,
+    // Weapon nodes, each has the Weapon script.
+    weapons: Vec<Handle<Node>>,
+    current_weapon: usize,
     collider: Handle<Node>,
+    health: f32,
+    max_health: f32,
+    // Additional camera pitch (in degrees) caused by weapon recoil.
+    recoil_pitch: f32,
+    // Positions of the rigid body at two last game ticks, the camera is rendered somewhere
+    // between them.
+    previous_position: Vector3<f32>,
+    current_position: Vector3<f32>,
+    shake: CameraShake,
 }
 
-async fn create_skybox(resource_manager: ResourceManager) -> SkyBox {
-    // Load skybox textures in parallel.
-    let (front, back, left, right, top, bottom) = fyrox::core::futures::join!(
-        resource_manager.request_texture("data/textures/skybox/front.jpg"),
-        resource_manager.request_texture("data/textures/skybox/back.jpg"),
-        resource_manager.request_texture("data/textures/skybox/left.jpg"),
-        resource_manager.request_texture("data/textures/skybox/right.jpg"),
-        resource_manager.request_texture("data/textures/skybox/up.jpg"),
-        resource_manager.request_texture("data/textures/skybox/down.jpg")
-    );
-
-    // Unwrap everything.
-    let skybox = SkyBoxBuilder {
-        front: Some(front.unwrap()),
-        back: Some(back.unwrap()),
-        left: Some(left.unwrap()),
-        right: Some(right.unwrap()),
-        top: Some(top.unwrap()),
-        bottom: Some(bottom.unwrap()),
-    }
-    .build()
-    .unwrap();
-
-    // Set S and T coordinate wrap mode, ClampToEdge will remove any possible seams on edges
-    // of the skybox.
-    let skybox_texture = skybox.cubemap().unwrap();
-    let mut data = skybox_texture.data_ref();
-    data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
-    data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
-
-    skybox
-}
-
+// Bullet impacts are reused, the effect is placed at the point of impact when it is acquired
+// from the pool, see `handle_hit`. Every kind of surface has its own look of impacts.
 fn create_bullet_impact(
     graph: &mut Graph,
     resource_manager: ResourceManager,
-    pos: Vector3<f32>,
-    orientation: UnitQuaternion<f32>,
+    surface: SurfaceKind,
 ) -> Handle<Node> {
+    let effect = surface.impact_effect();
+
     // Create sphere emitter first.
     let emitter = SphereEmitterBuilder::new(
         BaseEmitterBuilder::new()
-            .with_max_particles(200)
+            .with_max_particles(effect.max_particles)
             .with_spawn_rate(3000)
-            .with_size_modifier_range(-0.01..-0.0125)
-            .with_size_range(0.0075..0.015)
-            .with_lifetime_range(0.05..0.2)
-            .with_x_velocity_range(-0.0075..0.0075)
-            .with_y_velocity_range(-0.0075..0.0075)
-            .with_z_velocity_range(0.025..0.045)
+            .with_size_modifier_range(effect.size_modifier)
+            .with_size_range(effect.size)
+            .with_lifetime_range(effect.lifetime)
+            .with_x_velocity_range(-effect.spread..effect.spread)
+            .with_y_velocity_range(-effect.spread..effect.spread)
+            .with_z_velocity_range(effect.speed)
             .resurrect_particles(false),
     )
     .with_radius(0.01)
@@ -129,31 +221,144 @@ fn create_bullet_impact(
     // Color gradient will be used to modify color of each particle over its lifetime.
     let color_gradient = {
         let mut gradient = ColorGradient::new();
-        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 0, 0)));
-        gradient.add_point(GradientPoint::new(0.05, Color::from_rgba(255, 160, 0, 255)));
-        gradient.add_point(GradientPoint::new(0.95, Color::from_rgba(255, 120, 0, 255)));
-        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 60, 0, 0)));
+        for (location, color) in [0.0, 0.05, 0.95, 1.0].iter().zip(effect.colors.iter()) {
+            gradient.add_point(GradientPoint::new(*location, *color));
+        }
+        gradient
+    };
+
+    // Finally create particle system.
+    ParticleSystemBuilder::new(BaseBuilder::new())
+        .with_acceleration(Vector3::new(0.0, effect.gravity, 0.0))
+        .with_color_over_lifetime_gradient(color_gradient)
+        .with_emitters(vec![emitter])
+        // We'll use simple spark texture for each particle.
+        .with_texture(resource_manager.request_texture(asset_path("data/textures/spark.png")))
+        .build(graph)
+}
+
+// Rigid bodies with this tag are considered as something that can be damaged (bots, targets, etc.),
+// the tag can be set in the editor.
+const DAMAGEABLE_TAG: &str = "Damageable";
+
+// Surface kind is set by the tag of a collider, or by the tag of its rigid body if the collider
+// is not tagged. Damageable bodies are made of flesh, unless they're tagged otherwise.
+fn surface_kind(graph: &Graph, collider: Handle<Node>) -> SurfaceKind {
+    let body_tag = graph[graph[collider].parent()].tag();
+
+    SurfaceKind::from_tag(graph[collider].tag())
+        .or_else(|| SurfaceKind::from_tag(body_tag))
+        .unwrap_or(if body_tag == DAMAGEABLE_TAG {
+            SurfaceKind::Flesh
+        } else {
+            SurfaceKind::Default
+        })
+}
+
+// Static geometry is something that never moves (walls, floor, etc).
+fn is_static_geometry(graph: &Graph, collider: Handle<Node>) -> bool {
+    graph[graph[collider].parent()]
+        .cast::<RigidBody>()
+        .map_or(false, |body| body.body_type() == RigidBodyType::Static)
+}
+
+// Reacts to a hit of a bullet (or a projectile) that flew in given `direction`. Returns `true`
+// if something damageable was hit.
+fn handle_hit(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    impact_pools: &mut HashMap<SurfaceKind, EffectPool>,
+    intersection: &Intersection,
+    direction: Vector3<f32>,
+    damage: f32,
+) -> bool {
+    // Apply some force at the point of impact, the more damage a bullet makes, the harder it
+    // pushes. A collider is not necessarily attached to a rigid body, such hits push nothing.
+    let colliders_parent = graph[intersection.collider].parent();
+    let damageable = match graph[colliders_parent].cast_mut::<RigidBody>() {
+        Some(picked_rigid_body) => {
+            picked_rigid_body.apply_force_at_point(
+                direction.normalize().scale(damage),
+                intersection.position.coords,
+            );
+            picked_rigid_body.wake_up();
+
+            picked_rigid_body.tag() == DAMAGEABLE_TAG
+        }
+        None => false,
+    };
+
+    // Add bullet impact effect that matches the surface.
+    let effect_orientation = vector_to_quat(intersection.normal);
+
+    let surface = surface_kind(graph, intersection.collider);
+    // Pool of impacts of a surface is created when the surface is hit for the first time,
+    // impacts are shown for a second.
+    let impact = impact_pools
+        .entry(surface)
+        .or_insert_with(|| EffectPool::new(32, 1.0))
+        .acquire(graph, |graph| {
+            create_bullet_impact(graph, resource_manager, surface)
+        });
+
+    let impact = &mut graph[impact];
+    impact
+        .local_transform_mut()
+        .set_position(intersection.position.coords)
+        .set_rotation(effect_orientation);
+    // Reused effect could still have particles of the previous impact, so start it over.
+    impact.as_particle_system_mut().clear_particles();
+
+    damageable
+}
+
+// Muzzle flashes are reused, the flash is placed at the shot point when it is acquired from the
+// pool, see `shoot_weapon`.
+fn create_muzzle_flash(graph: &mut Graph, resource_manager: ResourceManager) -> Handle<Node> {
+    // Muzzle flash is a very short burst of big and bright particles that fly along the
+    // shot direction.
+    let emitter = SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(30)
+            .with_spawn_rate(3000)
+            .with_size_modifier_range(-0.2..-0.1)
+            .with_size_range(0.02..0.04)
+            .with_lifetime_range(0.02..0.05)
+            .with_x_velocity_range(-0.002..0.002)
+            .with_y_velocity_range(-0.002..0.002)
+            .with_z_velocity_range(0.005..0.015)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.005)
+    .build();
+
+    let color_gradient = {
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 0, 255)));
+        gradient.add_point(GradientPoint::new(0.50, Color::from_rgba(255, 200, 0, 255)));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 120, 0, 0)));
         gradient
     };
 
-    // Create new transform to orient and position particle system.
-    let transform = TransformBuilder::new()
-        .with_local_position(pos)
-        .with_local_rotation(orientation)
-        .build();
+    ParticleSystemBuilder::new(BaseBuilder::new())
+        .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+        .with_color_over_lifetime_gradient(color_gradient)
+        .with_emitters(vec![emitter])
+        .with_texture(resource_manager.request_texture(asset_path("data/textures/spark.png")))
+        .build(graph)
+}
 
-    // Finally create particle system with limited lifetime.
-    ParticleSystemBuilder::new(
+// The sound is played once at the given position, then the engine removes its node.
+fn play_dry_fire_sound(graph: &mut Graph, buffer: SoundBufferResource, pos: Vector3<f32>) {
+    SoundBuilder::new(
         BaseBuilder::new()
-            .with_lifetime(1.0)
-            .with_local_transform(transform),
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
     )
-    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
-    .with_color_over_lifetime_gradient(color_gradient)
-    .with_emitters(vec![emitter])
-    // We'll use simple spark texture for each particle.
-    .with_texture(resource_manager.request_texture(Path::new("data/textures/spark.png")))
-    .build(graph)
+    .with_buffer(Some(buffer))
+    .with_status(Status::Playing)
+    .with_play_once(true)
+    .with_gain(0.5)
+    .build(graph);
 }
 
 impl Player {
@@ -162,6 +367,12 @@ impl Player {
         resource_manager: ResourceManager,
         sender: Sender<Message>,
     ) -> Self {
+        // The camera works fine without a skybox, so the game can go on if it can't be loaded.
+        let skybox = load_skybox(resource_manager, "data/textures/skybox")
+            .await
+            .map_err(Log::err)
+            .ok();
+
         // Create rigid body with a camera, move it a bit up to "emulate" head.
         let camera;
         let weapon_pivot;
@@ -180,7 +391,7 @@ impl Player {
                             BaseBuilder::new()
                                 .with_local_transform(
                                     TransformBuilder::new()
-                                        .with_local_position(Vector3::new(0.0, 0.25, 0.0))
+                                        .with_local_position(Vector3::new(0.0, CAMERA_HEIGHT, 0.0))
                                         .build(),
                                 )
                                 .with_children(&[{
@@ -197,7 +408,6 @@ impl Player {
                                     weapon_pivot
                                 }]),
                         )
-                        .with_skybox(create_skybox(resource_manager).await)
                         .build(&mut scene.graph);
                         camera
                     },
@@ -216,22 +426,157 @@ impl Player {
         .with_can_sleep(false)
         .build(&mut scene.graph);
 
+        scene.graph[camera].as_camera_mut().set_skybox(skybox);
+
+        let position = **scene.graph[rigid_body_handle].local_transform().position();
+
         Self {
             camera,
             weapon_pivot,
             rigid_body: rigid_body_handle,
             controller: Default::default(),
+            key_bindings: Default::default(),
+            mouse_sensitivity: 0.5,
+            invert_y: false,
             sender,
             collider,
-            weapon: Default::default(), // Leave it unassigned for now.
+            weapons: Default::default(), // Leave it empty for now.
+            current_weapon: 0,
+            health: 100.0,
+            max_health: 100.0,
+            recoil_pitch: 0.0,
+            previous_position: position,
+            current_position: position,
+            shake: Default::default(),
         }
     }
 
-    fn update(&mut self, scene: &mut Scene) {
-        // Set pitch for the camera. These lines responsible for up-down camera rotation.
-        scene.graph[self.camera].local_transform_mut().set_rotation(
-            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.controller.pitch.to_radians()),
-        );
+    // Must be called after every tick of physics, it tracks where the rigid body was moved.
+    fn remember_position(&mut self, scene: &Scene) {
+        self.previous_position = self.current_position;
+        self.current_position = scene.graph[self.rigid_body].global_position();
+    }
+
+    // The rigid body moves only once per tick, and if the frame rate is higher than the tick
+    // rate, the camera would jump from one position to another. To make the movement smooth,
+    // the camera is moved between positions of the rigid body at two last ticks, `alpha` is a
+    // fraction of the tick passed since the last tick. The rigid body itself can't be moved,
+    // because it is driven by physics, so the camera is shifted relative to it instead.
+    fn interpolate(&self, scene: &mut Scene, alpha: f32) {
+        let interpolated = self.previous_position.lerp(&self.current_position, alpha);
+
+        // The offset is in world space, but the camera position is relative to the rigid body.
+        let body_rotation = **scene.graph[self.rigid_body].local_transform().rotation();
+        let offset = body_rotation.inverse() * (interpolated - self.current_position);
+
+        scene.graph[self.camera]
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, CAMERA_HEIGHT, 0.0) + offset + self.shake.offset());
+
+        // Global transforms are calculated once per tick in `engine.update`, the renderer uses
+        // them, so they must be recalculated to take the new camera position into account.
+        scene.graph.update_hierarchical_data();
+    }
+
+    fn kick_camera(&mut self, amount: f32) {
+        self.recoil_pitch += amount;
+    }
+
+    // Shakes the camera if the impact at given position is close enough to the player, the
+    // closer the impact and the more damage it makes, the harder the shake is.
+    fn shake_from_impact(&mut self, position: Vector3<f32>, damage: f32) {
+        let distance = (position - self.current_position).norm();
+        let falloff = (1.0 - distance / IMPACT_SHAKE_RADIUS).max(0.0);
+        self.shake.add_trauma(damage / 100.0 * falloff);
+    }
+
+    fn has_weapon(&self, graph: &Graph, kind: WeaponKind) -> bool {
+        self.weapons.iter().any(|weapon| {
+            graph[*weapon]
+                .try_get_script::<Weapon>()
+                .map_or(false, |weapon| weapon.kind() == kind)
+        })
+    }
+
+    // Adds rounds to the reserve of the weapon of the given kind. Returns `false` if the player
+    // has no such weapon or its reserve is full.
+    fn add_ammo(&self, graph: &mut Graph, kind: WeaponKind, amount: u32) -> bool {
+        for weapon in self.weapons.iter() {
+            if let Some(weapon) = graph[*weapon].try_get_script_mut::<Weapon>() {
+                if weapon.kind() == kind {
+                    return weapon.add_reserve_ammo(amount) > 0;
+                }
+            }
+        }
+        false
+    }
+
+    // Restores health, but not beyond its max value. Returns `false` if the health is full.
+    fn heal(&mut self, amount: f32) -> bool {
+        if self.health >= self.max_health {
+            return false;
+        }
+        self.health = (self.health + amount).min(self.max_health);
+        true
+    }
+
+    // Puts the weapon into the inventory and selects it. The inventory is kept in the order of
+    // `WEAPONS`, so number keys select weapons in the same order no matter in which order they
+    // were picked up.
+    fn add_weapon(&mut self, graph: &mut Graph, weapon: Handle<Node>) {
+        let slot = |graph: &Graph, weapon: Handle<Node>| {
+            graph[weapon]
+                .try_get_script::<Weapon>()
+                .and_then(|weapon| WEAPONS.iter().position(|kind| *kind == weapon.kind()))
+                .unwrap_or(WEAPONS.len())
+        };
+
+        let new_slot = slot(graph, weapon);
+        let index = self
+            .weapons
+            .iter()
+            .position(|other| slot(graph, *other) > new_slot)
+            .unwrap_or(self.weapons.len());
+
+        // "Attach" the weapon to the weapon pivot of the player.
+        graph.link_nodes(weapon, self.weapon_pivot);
+
+        self.weapons.insert(index, weapon);
+        self.current_weapon = index;
+    }
+
+    fn current_weapon(&self) -> Handle<Node> {
+        self.weapons
+            .get(self.current_weapon)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn select_weapon(&mut self, index: usize) {
+        // Ignore the request if there's no weapon in the slot.
+        if index < self.weapons.len() {
+            self.current_weapon = index;
+        }
+    }
+
+    fn next_weapon(&mut self) {
+        if !self.weapons.is_empty() {
+            self.current_weapon = (self.current_weapon + 1) % self.weapons.len();
+        }
+    }
+
+    fn prev_weapon(&mut self) {
+        if !self.weapons.is_empty() {
+            self.current_weapon =
+                (self.current_weapon + self.weapons.len() - 1) % self.weapons.len();
+        }
+    }
+
+    fn update(&mut self, scene: &mut Scene, dt: f32) {
+        // Recoil kicks the camera up, then it smoothly returns back.
+        self.recoil_pitch -= self.recoil_pitch * (10.0 * dt).min(1.0);
+
+        self.shake.update(dt);
 
         // Borrow rigid body node.
         let body = scene.graph[self.rigid_body].as_rigid_body_mut();
@@ -260,6 +605,8 @@ impl Player {
         // Finally new linear velocity.
         body.set_lin_vel(velocity);
 
+        let horizontal_speed = Vector3::new(velocity.x, 0.0, velocity.z).norm();
+
         // Change the rotation of the rigid body according to current yaw. These lines responsible for
         // left-right rotation.
         body.local_transform_mut()
@@ -268,13 +615,34 @@ impl Player {
                 self.controller.yaw.to_radians(),
             ));
 
+        // Current weapon bobs while the player moves.
+        if let Some(weapon) = scene.graph[self.current_weapon()].try_get_script_mut::<Weapon>() {
+            weapon.set_owner_speed(horizontal_speed);
+        }
+
         if self.controller.shoot {
             self.sender
                 .send(Message::ShootWeapon {
-                    weapon: self.weapon,
+                    weapon: self.current_weapon(),
+                    trigger_pulled: self.controller.trigger_pulled,
                 })
                 .unwrap();
         }
+
+        self.controller.trigger_pulled = false;
+    }
+
+    fn update_camera(&self, scene: &mut Scene) {
+        // Set pitch for the camera. These lines responsible for up-down camera rotation. The
+        // shake is applied on top of it, its position part is applied in `interpolate`.
+        scene.graph[self.camera].local_transform_mut().set_rotation(
+            UnitQuaternion::from_axis_angle(
+                &Vector3::x_axis(),
+                (self.controller.pitch - self.recoil_pitch)
+                    .clamp(-90.0, 90.0)
+                    .to_radians(),
+            ) * self.shake.rotation(),
+        );
     }
 
     fn process_input_event(&mut self, event: &Event<()>) {
@@ -282,38 +650,82 @@ impl Player {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::KeyboardInput { input, .. } => {
                     if let Some(key_code) = input.virtual_keycode {
+                        let bindings = &self.key_bindings;
                         match key_code {
-                            VirtualKeyCode::W => {
+                            _ if key_code == bindings.move_forward => {
                                 self.controller.move_forward = input.state == ElementState::Pressed;
                             }
-                            VirtualKeyCode::S => {
+                            _ if key_code == bindings.move_backward => {
                                 self.controller.move_backward =
                                     input.state == ElementState::Pressed;
                             }
-                            VirtualKeyCode::A => {
+                            _ if key_code == bindings.move_left => {
                                 self.controller.move_left = input.state == ElementState::Pressed;
                             }
-                            VirtualKeyCode::D => {
+                            _ if key_code == bindings.move_right => {
                                 self.controller.move_right = input.state == ElementState::Pressed;
                             }
+                            // Rebind movement keys.
+                            VirtualKeyCode::F2 if input.state == ElementState::Pressed => {
+                                self.key_bindings.toggle_arrow_keys();
+                            }
+                            VirtualKeyCode::R if input.state == ElementState::Pressed => {
+                                self.sender
+                                    .send(Message::ReloadWeapon {
+                                        weapon: self.current_weapon(),
+                                    })
+                                    .unwrap();
+                            }
+                            // Number keys select a weapon from the respective slot of the
+                            // inventory.
+                            VirtualKeyCode::Key1 if input.state == ElementState::Pressed => {
+                                self.select_weapon(0);
+                            }
+                            VirtualKeyCode::Key2 if input.state == ElementState::Pressed => {
+                                self.select_weapon(1);
+                            }
+                            VirtualKeyCode::Key3 if input.state == ElementState::Pressed => {
+                                self.select_weapon(2);
+                            }
+                            VirtualKeyCode::Key4 if input.state == ElementState::Pressed => {
+                                self.select_weapon(3);
+                            }
                             _ => (),
                         }
                     }
                 }
                 &WindowEvent::MouseInput { button, state, .. } => {
-                    if button == MouseButton::Left {
-                        self.controller.shoot = state == ElementState::Pressed;
+                    if button == self.key_bindings.shoot {
+                        let pressed = state == ElementState::Pressed;
+                        if pressed && !self.controller.shoot {
+                            self.controller.trigger_pulled = true;
+                        }
+                        self.controller.shoot = pressed;
+                    }
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    // Mouse wheel cycles through the weapons.
+                    let amount = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        MouseScrollDelta::PixelDelta(position) => position.y as f32,
+                    };
+                    if amount > 0.0 {
+                        self.next_weapon();
+                    } else if amount < 0.0 {
+                        self.prev_weapon();
                     }
                 }
                 _ => {}
             },
             Event::DeviceEvent { event, .. } => {
                 if let DeviceEvent::MouseMotion { delta } = event {
-                    let mouse_sens = 0.5;
-                    self.controller.yaw -= mouse_sens * delta.0 as f32;
+                    let y_sign = if self.invert_y { -1.0 } else { 1.0 };
 
-                    self.controller.pitch =
-                        (self.controller.pitch + mouse_sens * delta.1 as f32).clamp(-90.0, 90.0);
+                    self.controller.yaw -= self.mouse_sensitivity * delta.0 as f32;
+
+                    self.controller.pitch = (self.controller.pitch
+                        + y_sign * self.mouse_sensitivity * delta.1 as f32)
+                        .clamp(-90.0, 90.0);
                 }
             }
             _ => (),
@@ -321,21 +733,9 @@ impl Player {
     }
 }
 
-fn create_shot_trail(
-    graph: &mut Graph,
-    origin: Vector3<f32>,
-    direction: Vector3<f32>,
-    trail_length: f32,
-) {
-    let transform = TransformBuilder::new()
-        .with_local_position(origin)
-        // Scale the trail in XZ plane to make it thin, and apply `trail_length` scale on Y axis
-        // to stretch is out.
-        .with_local_scale(Vector3::new(0.0025, 0.0025, trail_length))
-        // Rotate the trail along given `direction`
-        .with_local_rotation(UnitQuaternion::face_towards(&direction, &Vector3::y()))
-        .build();
-
+// Shot trails are reused, the trail is placed along the shot when it is acquired from the pool,
+// see `place_shot_trail`.
+fn create_shot_trail(graph: &mut Graph) -> Handle<Node> {
     // Create unit cylinder with caps that faces toward Z axis.
     let shape = SurfaceSharedData::new(SurfaceData::make_cylinder(
         6,     // Count of sides
@@ -359,11 +759,7 @@ fn create_shot_trail(
     MeshBuilder::new(
         BaseBuilder::new()
             // Do not cast shadows.
-            .with_cast_shadows(false)
-            .with_local_transform(transform)
-            // Shot trail should live ~0.25 seconds, after that it will be automatically
-            // destroyed.
-            .with_lifetime(0.25),
+            .with_cast_shadows(false),
     )
     .with_surfaces(vec![SurfaceBuilder::new(shape)
         .with_material(SharedMaterial::new(material))
@@ -371,76 +767,505 @@ fn create_shot_trail(
     // Make sure to set Forward render path, otherwise the object won't be
     // transparent.
     .with_render_path(RenderPath::Forward)
-    .build(graph);
+    .build(graph)
+}
+
+fn place_shot_trail(
+    graph: &mut Graph,
+    trail: Handle<Node>,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    trail_length: f32,
+) {
+    graph[trail]
+        .local_transform_mut()
+        .set_position(origin)
+        // Scale the trail in XZ plane to make it thin, and apply `trail_length` scale on Y axis
+        // to stretch is out.
+        .set_scale(Vector3::new(0.0025, 0.0025, trail_length))
+        // Rotate the trail along given `direction`
+        .set_rotation(UnitQuaternion::face_towards(&direction, &Vector3::y()));
+}
+
+// Time (in seconds) during which hit marker is shown.
+const HIT_MARKER_TIME: f32 = 0.15;
+
+fn create_hit_marker_line(
+    ctx: &mut BuildContext,
+    horizontal_alignment: HorizontalAlignment,
+    vertical_alignment: VerticalAlignment,
+) -> Handle<UiNode> {
+    // Lines at the left and right sides are horizontal, the rest are vertical.
+    let (width, height) = if vertical_alignment == VerticalAlignment::Center {
+        (10.0, 2.0)
+    } else {
+        (2.0, 10.0)
+    };
+
+    BorderBuilder::new(
+        WidgetBuilder::new()
+            .with_width(width)
+            .with_height(height)
+            .with_horizontal_alignment(horizontal_alignment)
+            .with_vertical_alignment(vertical_alignment)
+            .with_background(Brush::Solid(Color::RED)),
+    )
+    .build(ctx)
 }
 
 struct Game {
     scene: Handle<Scene>,
     player: Player,
-    weapons: Pool<Weapon>,
+    projectiles: Pool<Projectile>,
     receiver: Receiver<Message>,
     sender: Sender<Message>,
+    hud: Handle<UiNode>,
+    ammo_text: Handle<UiNode>,
+    health_text: Handle<UiNode>,
+    hit_marker: Handle<UiNode>,
+    // Hit marker is shown while this timer is above zero.
+    hit_marker_timer: f32,
+    // Paused game does not process any input and its scene is frozen, but still rendered.
+    paused: bool,
+    fps_counter: FpsCounter,
+    fullscreen: FullscreenToggle,
+    // Debug camera, it keeps the transform the player's camera had before it was taken. The
+    // player gets no input while it flies.
+    free_camera: FreeCamera,
+    // Effects that are shown very often are reused instead of being created for every shot.
+    impact_pools: HashMap<SurfaceKind, EffectPool>,
+    trail_pool: EffectPool,
+    muzzle_flash_pool: EffectPool,
+    // Bullet holes on static geometry, their lifetime and max count could be changed at any
+    // time.
+    bullet_decals: BulletDecals,
+    // Closing the window must be confirmed in this dialog when `confirm_quit` is set.
+    confirm_quit: bool,
+    quit_dialog: QuitDialog,
+    // The game is paused while the quit dialog is open, this is the state before the dialog
+    // was opened, it is restored when the player decides to stay.
+    paused_before_quit: bool,
+    // The click of a weapon with empty magazine, the game is silent if it can't be made.
+    dry_fire_sound: Option<SoundBufferResource>,
+    // Models of every weapon, they're loaded on start, so a weapon that was picked up is created
+    // without waiting. A weapon whose model can't be loaded is not in the map.
+    weapon_models: HashMap<WeaponKind, Model>,
 }
 
 impl Game {
-    pub async fn new(engine: &mut Engine) -> Self {
+    pub async fn new(engine: &mut Engine, headless: bool) -> Self {
         // Make message queue.
         let (sender, receiver) = mpsc::channel();
 
         let mut scene = Scene::new();
 
-        // Load a scene resource and create its instance.
-        engine
+        // Load a scene resource and create its instance. A missing or corrupted scene file
+        // must not crash the game, so the error is logged and the game goes on with an empty
+        // scene.
+        let path = "data/models/scene.rgs";
+        match engine
             .resource_manager
-            .request_model("data/models/scene.rgs")
+            .request_model(asset_path(path))
             .await
-            .unwrap()
-            .instantiate(&mut scene);
+        {
+            Ok(model) => {
+                model.instantiate(&mut scene);
+            }
+            Err(err) => Log::err(format!("Unable to load scene {}! Reason: {:?}", path, err)),
+        }
 
         // Create player first.
         let mut player =
             Player::new(&mut scene, engine.resource_manager.clone(), sender.clone()).await;
 
-        // Create weapon next.
-        let weapon = Weapon::new(&mut scene, engine.resource_manager.clone()).await;
+        // Load models of all weapons in parallel.
+        let models = join_all(
+            WEAPONS
+                .iter()
+                .map(|kind| load_weapon_model(engine.resource_manager.clone(), *kind)),
+        )
+        .await;
+        let weapon_models = WEAPONS
+            .iter()
+            .zip(models)
+            .filter_map(|(kind, model)| Some((*kind, model?)))
+            .collect::<HashMap<_, _>>();
 
-        // "Attach" the weapon to the weapon pivot of the player.
-        scene.graph.link_nodes(weapon.model(), player.weapon_pivot);
+        // Create the first weapon next and "give" it to the player. Each weapon is a scene node
+        // with the Weapon script, the script is updated by the engine.
+        let weapon = create_weapon(&mut scene, weapon_models.get(&WEAPONS[0]), WEAPONS[0]);
+        player.add_weapon(&mut scene.graph, weapon);
 
-        // Create a container for the weapons.
-        let mut weapons = Pool::new();
+        // The rest of the weapons are lying in a row in front of the player.
+        for (i, kind) in WEAPONS.iter().skip(1).enumerate() {
+            create_weapon_pickup(
+                &mut scene,
+                engine.resource_manager.clone(),
+                *kind,
+                Vector3::new(i as f32 - 1.0, 0.5, 1.0),
+            )
+            .await;
+        }
 
-        // Put the weapon into it - this operation moves the weapon in the pool and returns handle.
-        let weapon = weapons.spawn(weapon);
+        // Ammo for every weapon is lying in the next row, and there's some health nearby.
+        for (i, kind) in WEAPONS.iter().enumerate() {
+            create_ammo_pickup(
+                &mut scene,
+                *kind,
+                kind.magazine_size() * 2,
+                Vector3::new(i as f32 - 1.5, 0.3, 2.0),
+            );
+        }
+        create_health_pickup(&mut scene, 25.0, Vector3::new(2.0, 0.3, 1.0));
 
-        // "Give" the weapon to the player.
-        player.weapon = weapon;
+        // Create HUD. Every widget is placed into a grid that is stretched over the whole
+        // window. Widgets that are put directly on the root canvas have zero size unless it is
+        // set explicitly, so for example a text would be invisible. Grid on the other hand
+        // gives its children the size of its cell, and the children then can be aligned
+        // inside of it.
+        let ctx = &mut engine.user_interface.build_ctx();
+        let (frame_width, frame_height) = if headless {
+            HEADLESS_FRAME_SIZE
+        } else {
+            engine.renderer.get_frame_size()
+        };
+        let ammo_text;
+        let health_text;
+        let hit_marker;
+        let hud = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_width as f32)
+                .with_height(frame_height as f32)
+                // Crosshair is made of two thin bars at the center of the screen.
+                .with_child(
+                    BorderBuilder::new(
+                        WidgetBuilder::new()
+                            .with_width(16.0)
+                            .with_height(2.0)
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_background(Brush::Solid(Color::WHITE)),
+                    )
+                    .build(ctx),
+                )
+                .with_child(
+                    BorderBuilder::new(
+                        WidgetBuilder::new()
+                            .with_width(2.0)
+                            .with_height(16.0)
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_background(Brush::Solid(Color::WHITE)),
+                    )
+                    .build(ctx),
+                )
+                // Hit marker is four short lines around the crosshair, it is hidden until
+                // something damageable was hit.
+                .with_child({
+                    hit_marker = GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_width(40.0)
+                            .with_height(40.0)
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_visibility(false)
+                            .with_child(create_hit_marker_line(
+                                ctx,
+                                HorizontalAlignment::Left,
+                                VerticalAlignment::Center,
+                            ))
+                            .with_child(create_hit_marker_line(
+                                ctx,
+                                HorizontalAlignment::Right,
+                                VerticalAlignment::Center,
+                            ))
+                            .with_child(create_hit_marker_line(
+                                ctx,
+                                HorizontalAlignment::Center,
+                                VerticalAlignment::Top,
+                            ))
+                            .with_child(create_hit_marker_line(
+                                ctx,
+                                HorizontalAlignment::Center,
+                                VerticalAlignment::Bottom,
+                            )),
+                    )
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .build(ctx);
+                    hit_marker
+                })
+                // Ammo counter is at the bottom right corner.
+                .with_child({
+                    ammo_text = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_horizontal_alignment(HorizontalAlignment::Right)
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .with_margin(Thickness::uniform(20.0)),
+                    )
+                    .build(ctx);
+                    ammo_text
+                })
+                // Health is at the bottom left corner.
+                .with_child({
+                    health_text = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_horizontal_alignment(HorizontalAlignment::Left)
+                            .with_vertical_alignment(VerticalAlignment::Bottom)
+                            .with_margin(Thickness::uniform(20.0)),
+                    )
+                    .build(ctx);
+                    health_text
+                }),
+        )
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
 
         Self {
             player,
             scene: engine.scenes.add(scene),
-            weapons,
+            projectiles: Default::default(),
             sender,
             receiver,
+            hud,
+            ammo_text,
+            health_text,
+            hit_marker,
+            hit_marker_timer: 0.0,
+            paused: false,
+            fps_counter: FpsCounter::new(&mut engine.user_interface),
+            fullscreen: Default::default(),
+            free_camera: Default::default(),
+            impact_pools: Default::default(),
+            // Shot trails are shown for a quarter of a second.
+            trail_pool: EffectPool::new(32, 0.25),
+            // Flash should only "pop" for a moment, so it is shown only for 0.05 seconds.
+            muzzle_flash_pool: EffectPool::new(8, 0.05),
+            bullet_decals: BulletDecals::new(64, 10.0),
+            confirm_quit: true,
+            quit_dialog: QuitDialog::new(&mut engine.user_interface),
+            paused_before_quit: false,
+            dry_fire_sound: create_dry_fire_sound_buffer(),
+            weapon_models,
         }
     }
 
-    fn shoot_weapon(&mut self, weapon: Handle<Weapon>, engine: &mut Engine) {
-        let weapon = &mut self.weapons[weapon];
+    // Input events are not passed to the player while the game is paused or while the free
+    // camera flies, so everything that was held must be released, otherwise the player would
+    // keep walking or shooting after that.
+    fn release_player_input(&mut self) {
+        let controller = &mut self.player.controller;
+        *controller = InputController {
+            pitch: controller.pitch,
+            yaw: controller.yaw,
+            ..Default::default()
+        };
+    }
+
+    pub fn toggle_pause(&mut self, engine: &mut Engine) {
+        self.paused = !self.paused;
+
+        self.release_player_input();
+
+        *engine.scenes[self.scene].graph.physics.enabled = !self.paused;
+    }
+
+    pub fn toggle_free_camera(&mut self, engine: &mut Engine) {
+        self.release_player_input();
 
-        if weapon.can_shoot() {
-            weapon.shoot();
+        self.free_camera
+            .toggle(&mut engine.scenes[self.scene].graph, self.player.camera);
+    }
+
+    // Called when the window is about to be closed, returns `true` if the game must exit right
+    // away, otherwise the player will be asked first.
+    pub fn request_quit(&mut self, engine: &mut Engine) -> bool {
+        if !self.confirm_quit {
+            return true;
+        }
+
+        if !self.quit_dialog.is_open() {
+            self.paused_before_quit = self.paused;
+            if !self.paused {
+                self.toggle_pause(engine);
+            }
+
+            self.quit_dialog.set_open(&engine.user_interface, true);
+        }
+
+        false
+    }
+
+    pub fn close_quit_dialog(&mut self, engine: &mut Engine) {
+        self.quit_dialog.set_open(&engine.user_interface, false);
+
+        if self.paused != self.paused_before_quit {
+            self.toggle_pause(engine);
+        }
+    }
+
+    // Returns `true` if the player has confirmed that the game must exit.
+    pub fn handle_ui_message(&mut self, engine: &mut Engine, message: &UiMessage) -> bool {
+        match self.quit_dialog.answer(message) {
+            Some(QuitAnswer::Yes) => true,
+            Some(QuitAnswer::No) => {
+                self.close_quit_dialog(engine);
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn remember_positions(&mut self, engine: &Engine) {
+        self.player.remember_position(&engine.scenes[self.scene]);
+    }
 
-            let scene = &mut engine.scenes[self.scene];
+    pub fn interpolate(&self, engine: &mut Engine, alpha: f32) {
+        // The free camera moves on its own, it does not follow the player.
+        if !self.free_camera.is_active() {
+            self.player
+                .interpolate(&mut engine.scenes[self.scene], alpha);
+        }
+    }
+
+    // HUD must always cover the whole window, so it must be resized together with the window.
+    fn resize_hud(&self, engine: &mut Engine, width: f32, height: f32) {
+        engine.user_interface.send_message(WidgetMessage::width(
+            self.hud,
+            MessageDirection::ToWidget,
+            width,
+        ));
+        engine.user_interface.send_message(WidgetMessage::height(
+            self.hud,
+            MessageDirection::ToWidget,
+            height,
+        ));
+    }
+
+    // Gives the player contents of the pickups the player has walked into. A pickup that gives
+    // nothing (a weapon the player already has, ammo for a full reserve, health when it is
+    // full) stays in the world.
+    fn update_pickups(&mut self, engine: &mut Engine) {
+        let scene = &mut engine.scenes[self.scene];
+
+        for (pickup, item) in find_touched_pickups(&scene.graph, self.player.collider) {
+            let taken = match item {
+                PickupItem::Weapon(kind) => {
+                    if self.player.has_weapon(&scene.graph, kind) {
+                        false
+                    } else {
+                        // The model of the weapon was loaded on start, so the weapon is created
+                        // instantly.
+                        let weapon = create_weapon(scene, self.weapon_models.get(&kind), kind);
+                        self.player.add_weapon(&mut scene.graph, weapon);
+                        true
+                    }
+                }
+                PickupItem::Ammo {
+                    weapon_kind,
+                    amount,
+                } => self.player.add_ammo(&mut scene.graph, weapon_kind, amount),
+                PickupItem::Health(amount) => self.player.heal(amount),
+            };
+
+            if taken {
+                scene.graph.remove_node(pickup);
+            }
+        }
+    }
+
+    fn shoot_weapon(&mut self, weapon: Handle<Node>, trigger_pulled: bool, engine: &mut Engine) {
+        let scene = &mut engine.scenes[self.scene];
+
+        let (kind, shot_point, camera_kick) =
+            match scene.graph[weapon].try_get_script_mut::<Weapon>() {
+                Some(weapon_script) => {
+                    // Semi-automatic weapons make only one shot per trigger pull.
+                    if !weapon_script.kind().is_automatic() && !trigger_pulled {
+                        return;
+                    }
+
+                    if weapon_script.is_empty() {
+                        let clicked = weapon_script.dry_fire();
+                        let shot_point = weapon_script.shot_point();
+
+                        match self.dry_fire_sound.clone() {
+                            Some(buffer) if clicked => {
+                                let pos = scene.graph[shot_point].global_position();
+                                play_dry_fire_sound(&mut scene.graph, buffer, pos);
+                            }
+                            _ => (),
+                        }
+
+                        return;
+                    }
+
+                    if !weapon_script.can_shoot() {
+                        return;
+                    }
+
+                    (
+                        weapon_script.kind(),
+                        weapon_script.shot_point(),
+                        weapon_script.shoot(),
+                    )
+                }
+                None => return,
+            };
+
+        self.player.kick_camera(camera_kick);
+        self.player.shake.add_trauma(kind.shake_trauma());
+
+        let weapon_model = &scene.graph[weapon];
+        let look = weapon_model.look_vector().normalize();
+        let side = weapon_model.side_vector().normalize();
+        let up = weapon_model.up_vector().normalize();
+
+        let origin = scene.graph[shot_point].global_position();
+
+        // Add muzzle flash at the shot point, the flash is oriented along the shot direction.
+        let resource_manager = engine.resource_manager.clone();
+        let flash = self
+            .muzzle_flash_pool
+            .acquire(&mut scene.graph, move |graph| {
+                create_muzzle_flash(graph, resource_manager)
+            });
+        let flash = &mut scene.graph[flash];
+        flash
+            .local_transform_mut()
+            .set_position(origin)
+            .set_rotation(vector_to_quat(look));
+        // Reused flash could still have particles of the previous shot, so start it over.
+        flash.as_particle_system_mut().clear_particles();
+
+        // Pellets are spread randomly in a cone, `spread` is a max offset of a direction
+        // from the cone axis.
+        let spread = kind.spread().to_radians().tan();
+        let mut rng = fyrox::rand::thread_rng();
+
+        for _ in 0..kind.pellet_count() {
+            let direction = if spread > 0.0 {
+                look + side.scale(rng.gen_range(-spread..spread))
+                    + up.scale(rng.gen_range(-spread..spread))
+            } else {
+                look
+            };
 
-            let weapon_model = &scene.graph[weapon.model()];
+            // Projectile weapons launch a projectile that will hit something later on.
+            if let Some(speed) = kind.projectile_speed() {
+                self.projectiles.spawn(Projectile::new(
+                    &mut scene.graph,
+                    origin,
+                    direction.normalize().scale(speed),
+                    kind.damage(),
+                ));
+                continue;
+            }
 
             // Make a ray that starts at the weapon's position in the world and look toward
-            // "look" vector of the weapon.
-            let ray = Ray::new(
-                scene.graph[weapon.shot_point()].global_position(),
-                weapon_model.look_vector().scale(1000.0),
-            );
+            // the direction of the bullet (or pellet).
+            let ray = Ray::new(origin, direction.normalize().scale(1000.0));
 
             let mut intersections = Vec::new();
 
@@ -460,28 +1285,25 @@ impl Game {
                 .iter()
                 .find(|i| i.collider != self.player.collider)
             {
-                //
-                // TODO: Add code to handle intersections with bots.
-                //
-
-                // For now just apply some force at the point of impact.
-                let colliders_parent = scene.graph[intersection.collider].parent();
-                let picked_rigid_body = scene.graph[colliders_parent].as_rigid_body_mut();
-                picked_rigid_body.apply_force_at_point(
-                    ray.dir.normalize().scale(10.0),
-                    intersection.position.coords,
-                );
-                picked_rigid_body.wake_up();
-
-                // Add bullet impact effect.
-                let effect_orientation = vector_to_quat(intersection.normal);
-
-                create_bullet_impact(
+                if handle_hit(
                     &mut scene.graph,
                     engine.resource_manager.clone(),
-                    intersection.position.coords,
-                    effect_orientation,
-                );
+                    &mut self.impact_pools,
+                    intersection,
+                    ray.dir,
+                    kind.damage(),
+                ) {
+                    self.hit_marker_timer = HIT_MARKER_TIME;
+                } else if is_static_geometry(&scene.graph, intersection.collider) {
+                    self.bullet_decals.add(
+                        &mut scene.graph,
+                        intersection.position.coords,
+                        intersection.normal,
+                    );
+                }
+
+                self.player
+                    .shake_from_impact(intersection.position.coords, kind.damage());
 
                 // Trail length will be the length of line between intersection point and ray origin.
                 (intersection.position.coords - ray.origin).norm()
@@ -490,32 +1312,179 @@ impl Game {
                 ray.dir.norm()
             };
 
-            create_shot_trail(&mut scene.graph, ray.origin, ray.dir, trail_length);
+            let trail = self.trail_pool.acquire(&mut scene.graph, create_shot_trail);
+            place_shot_trail(&mut scene.graph, trail, ray.origin, ray.dir, trail_length);
         }
     }
 
     pub fn update(&mut self, engine: &mut Engine, dt: f32) {
+        self.fps_counter.update(&engine.user_interface);
+
+        // The free camera flies even while the game is paused, so a frozen scene could be
+        // inspected from any side.
+        self.free_camera
+            .update(&mut engine.scenes[self.scene].graph, dt);
+
+        if self.paused {
+            return;
+        }
+
+        self.update_pickups(engine);
+
         let scene = &mut engine.scenes[self.scene];
 
-        self.player.update(scene);
+        self.player.update(scene, dt);
+        if !self.free_camera.is_active() {
+            self.player.update_camera(scene);
+        }
+
+        for impact_pool in self.impact_pools.values_mut() {
+            impact_pool.update(&mut scene.graph, dt);
+        }
+        self.trail_pool.update(&mut scene.graph, dt);
+        self.muzzle_flash_pool.update(&mut scene.graph, dt);
+        self.bullet_decals.update(&mut scene.graph, dt);
 
-        for weapon in self.weapons.iter_mut() {
-            weapon.update(dt, &mut scene.graph);
+        // Only the current weapon is visible, the rest are hidden until selected.
+        for (index, weapon) in self.player.weapons.iter().enumerate() {
+            scene.graph[*weapon].set_visibility(index == self.player.current_weapon);
+        }
+
+        // Move projectiles and check if they hit something, a projectile is destroyed on
+        // impact or when it flew for too long.
+        let mut dead_projectiles = Vec::new();
+        for (handle, projectile) in self.projectiles.pair_iter_mut() {
+            if let Some(intersection) =
+                projectile.update(&mut scene.graph, dt, self.player.collider)
+            {
+                if handle_hit(
+                    &mut scene.graph,
+                    engine.resource_manager.clone(),
+                    &mut self.impact_pools,
+                    &intersection,
+                    projectile.velocity(),
+                    projectile.damage(),
+                ) {
+                    self.hit_marker_timer = HIT_MARKER_TIME;
+                }
+
+                self.player
+                    .shake_from_impact(intersection.position.coords, projectile.damage());
+
+                dead_projectiles.push(handle);
+            } else if projectile.is_expired() {
+                dead_projectiles.push(handle);
+            }
+        }
+        for handle in dead_projectiles {
+            let projectile = self.projectiles.free(handle);
+            scene.graph.remove_node(projectile.model());
         }
 
         // We're using `try_recv` here because we don't want to wait until next message -
         // if the queue is empty just continue to next frame.
         while let Ok(message) = self.receiver.try_recv() {
             match message {
-                Message::ShootWeapon { weapon } => {
-                    self.shoot_weapon(weapon, engine);
+                Message::ShootWeapon {
+                    weapon,
+                    trigger_pulled,
+                } => {
+                    self.shoot_weapon(weapon, trigger_pulled, engine);
+                }
+                Message::ReloadWeapon { weapon } => {
+                    let scene = &mut engine.scenes[self.scene];
+                    if let Some(weapon) = scene.graph[weapon].try_get_script_mut::<Weapon>() {
+                        weapon.reload();
+                    }
                 }
             }
         }
+
+        self.hit_marker_timer = (self.hit_marker_timer - dt).max(0.0);
+        engine
+            .user_interface
+            .send_message(WidgetMessage::visibility(
+                self.hit_marker,
+                MessageDirection::ToWidget,
+                self.hit_marker_timer > 0.0,
+            ));
+
+        // Show the ammo of current weapon.
+        let scene = &engine.scenes[self.scene];
+        let ammo = match scene.graph[self.player.current_weapon()].try_get_script::<Weapon>() {
+            Some(weapon) if weapon.is_reloading() => "Reloading...".to_owned(),
+            Some(weapon) => format!("{} / {}", weapon.ammo_in_mag(), weapon.reserve_ammo()),
+            None => Default::default(),
+        };
+        engine.user_interface.send_message(TextMessage::text(
+            self.ammo_text,
+            MessageDirection::ToWidget,
+            ammo,
+        ));
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.health_text,
+            MessageDirection::ToWidget,
+            format!("Health: {:.0}", self.player.health),
+        ));
+    }
+}
+
+// A single tick of the game logic, it is the same with and without a window.
+fn update_game(
+    game: &mut Game,
+    engine: &mut Engine,
+    control_flow: &mut ControlFlow,
+    lag: &mut f32,
+) {
+    // Run our game's logic.
+    game.update(engine, TIMESTEP);
+
+    // Handle messages from the UI, this must be done before the engine update, otherwise the
+    // engine would take them.
+    while let Some(message) = engine.user_interface.poll_message() {
+        if game.handle_ui_message(engine, &message) {
+            *control_flow = ControlFlow::Exit;
+        }
+    }
+
+    // Update engine each frame. While the game is paused the engine is updated with zero time
+    // step, this freezes scripts, particles and timers of the scene, but the UI keeps working.
+    let dt = if game.paused { 0.0 } else { TIMESTEP };
+    engine.update(dt, control_flow, lag, Default::default());
+
+    game.remember_positions(engine);
+}
+
+// Runs the game without a window: the engine was created with `headless` flag of
+// `EngineInitParams`, which keeps the window hidden, and this loop never renders a frame. The
+// engine still creates its graphics context and renderer, the game just doesn't use them. The
+// event loop is not run either, nobody sends events to a game without a window, so the player
+// gets no input at all and just stands still. The game logic is updated at the same fixed rate,
+// the loop sleeps between ticks instead of rendering frames.
+fn run_headless(mut game: Game, mut engine: Engine) {
+    let mut control_flow = ControlFlow::Poll;
+    let mut previous = time::Instant::now();
+    let mut lag = 0.0;
+
+    while control_flow != ControlFlow::Exit {
+        lag += previous.elapsed().as_secs_f32();
+        previous = time::Instant::now();
+        lag = lag.min(MAX_SUBSTEPS as f32 * TIMESTEP);
+
+        while lag >= TIMESTEP {
+            lag -= TIMESTEP;
+
+            update_game(&mut game, &mut engine, &mut control_flow, &mut lag);
+        }
+
+        thread::sleep(time::Duration::from_secs_f32((TIMESTEP - lag).max(0.0)));
     }
 }
 
 fn main() {
+    let headless = is_headless();
+
     // Configure main window first.
     let window_builder = WindowBuilder::new().with_title("3D Shooter Tutorial");
     // Create event loop that will be used to "listen" events from the OS.
@@ -523,18 +1492,43 @@ fn main() {
 
     // Finally create an instance of the engine.
     let serialization_context = Arc::new(SerializationContext::new());
+
+    // Register scripts, so they could be saved with the scene.
+    serialization_context
+        .script_constructors
+        .add::<Weapon>("Weapon");
+    serialization_context
+        .script_constructors
+        .add::<WeaponPickup>("Weapon Pickup");
+    serialization_context
+        .script_constructors
+        .add::<AmmoPickup>("Ammo Pickup");
+    serialization_context
+        .script_constructors
+        .add::<HealthPickup>("Health Pickup");
+
     let mut engine = Engine::new(EngineInitParams {
         window_builder,
         resource_manager: ResourceManager::new(serialization_context.clone()),
         serialization_context,
         events_loop: &event_loop,
         vsync: false,
-        headless: false,
+        // The window stays hidden in headless mode.
+        headless,
     })
     .unwrap();
 
+    // Scripts are processed only when plugins are enabled. There are no plugins in this
+    // tutorial, so this just makes the engine to update the scripts.
+    engine.enable_plugins(Default::default(), true);
+
     // Initialize game instance.
-    let mut game = fyrox::core::futures::executor::block_on(Game::new(&mut engine));
+    let mut game = fyrox::core::futures::executor::block_on(Game::new(&mut engine, headless));
+
+    if headless {
+        run_headless(game, engine);
+        return;
+    }
 
     // Run the event loop of the main window. which will respond to OS and window events and update
     // engine's state accordingly. Engine lets you to decide which event should be handled,
@@ -542,7 +1536,22 @@ fn main() {
     let mut previous = time::Instant::now();
     let mut lag = 0.0;
     event_loop.run(move |event, _, control_flow| {
-        game.player.process_input_event(&event);
+        if !game.paused && !game.free_camera.is_active() {
+            game.player.process_input_event(&event);
+        }
+
+        game.free_camera.process_os_event(&event);
+
+        game.fullscreen
+            .process_os_event(&event, engine.get_window());
+
+        // The UI must know about mouse and keyboard events too, otherwise nobody will be able
+        // to click its buttons.
+        if let Event::WindowEvent { event, .. } = &event {
+            if let Some(os_event) = translate_event(event) {
+                engine.user_interface.process_os_event(&os_event);
+            }
+        }
 
         match event {
             Event::MainEventsCleared => {
@@ -552,36 +1561,78 @@ fn main() {
                 let elapsed = previous.elapsed();
                 previous = time::Instant::now();
                 lag += elapsed.as_secs_f32();
+
+                // If a frame took too long (for example while the window is dragged), the
+                // game would try to catch up by running a lot of ticks at once, which makes
+                // the next frame even longer, and so on until the game freezes completely.
+                // This is known as the "spiral of death". To prevent it, the amount of ticks
+                // per frame is limited and the time beyond the limit is dropped, so the game
+                // just slows down for a moment.
+                lag = lag.min(MAX_SUBSTEPS as f32 * TIMESTEP);
+
                 while lag >= TIMESTEP {
                     lag -= TIMESTEP;
 
-                    // Run our game's logic.
-                    game.update(&mut engine, TIMESTEP);
-
-                    // Update engine each frame.
-                    engine.update(TIMESTEP, control_flow, &mut lag, Default::default());
+                    update_game(&mut game, &mut engine, control_flow, &mut lag);
                 }
 
                 // Rendering must be explicitly requested and handled after RedrawRequested event is received.
                 engine.get_window().request_redraw();
             }
             Event::RedrawRequested(_) => {
-                // Render at max speed - it is not tied to the game code.
+                // Render at max speed - it is not tied to the game code. Rendering usually
+                // happens between ticks of the game, the time that is left in `lag` after the
+                // ticks tells how far the rendered frame is from the last tick, it is used to
+                // blend positions of moving objects. After rendering everything is moved back,
+                // so the game logic will see positions of the last tick.
+                game.interpolate(&mut engine, lag / TIMESTEP);
                 engine.render().unwrap();
+                game.interpolate(&mut engine, 1.0);
+
+                game.fps_counter.register_frame();
             }
             Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                WindowEvent::KeyboardInput { input, .. } => {
-                    // Exit game by hitting Escape.
-                    if let Some(VirtualKeyCode::Escape) = input.virtual_keycode {
+                WindowEvent::CloseRequested => {
+                    if game.request_quit(&mut engine) {
                         *control_flow = ControlFlow::Exit
                     }
                 }
+                WindowEvent::KeyboardInput { input, .. } => {
+                    // Pause or unpause the game by hitting Escape. This is handled outside of
+                    // the game, so it works while the game is paused. If the quit dialog is
+                    // open, Escape closes it, just like the "No" button.
+                    if input.state == ElementState::Pressed
+                        && input.virtual_keycode == Some(VirtualKeyCode::Escape)
+                    {
+                        if game.quit_dialog.is_open() {
+                            game.close_quit_dialog(&mut engine);
+                        } else {
+                            game.toggle_pause(&mut engine);
+                        }
+                    }
+
+                    // Show or hide frame rate counter by hitting F3.
+                    if input.state == ElementState::Pressed
+                        && input.virtual_keycode == Some(VirtualKeyCode::F3)
+                    {
+                        game.fps_counter.toggle(&engine.user_interface);
+                    }
+
+                    // Let the camera fly around the scene by hitting F4, hitting it again
+                    // returns the camera to the player.
+                    if input.state == ElementState::Pressed
+                        && input.virtual_keycode == Some(VirtualKeyCode::F4)
+                    {
+                        game.toggle_free_camera(&mut engine);
+                    }
+                }
                 WindowEvent::Resized(size) => {
                     // It is very important to handle Resized event from window, because
                     // renderer knows nothing about window size - it must be notified
                     // directly when window size has changed.
                     engine.set_frame_size(size.into()).unwrap();
+
+                    game.resize_hud(&mut engine, size.width as f32, size.height as f32);
                 }
                 _ => (),
             },
@@ -1,6 +1,14 @@
-use crate::weapon::Weapon;
-use fyrox::core::pool::Handle;
+use fyrox::{core::pool::Handle, scene::node::Node};
 
 pub enum Message {
-    ShootWeapon { weapon: Handle<Weapon> },
+    // Weapons are scene nodes with the Weapon script.
+    ShootWeapon {
+        weapon: Handle<Node>,
+        // `true` only when the trigger was pulled this frame, semi-automatic weapons ignore
+        // held trigger.
+        trigger_pulled: bool,
+    },
+    ReloadWeapon {
+        weapon: Handle<Node>,
+    },
 }
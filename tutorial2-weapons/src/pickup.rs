@@ -0,0 +1,343 @@
+use crate::weapon::WeaponKind;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        pool::Handle,
+        reflect::prelude::*,
+        sstorage::ImmutableString,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_component_provider,
+    material::{Material, PropertyValue, SharedMaterial},
+    scene::{
+        base::BaseBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder,
+        },
+        node::{Node, TypeUuidProvider},
+        pivot::PivotBuilder,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+    utils::log::Log,
+};
+use tutorial_common::assets::asset_path;
+
+// Common part of every pickup. A pickup script must be assigned to a static rigid body with a
+// sensor collider, the game checks the sensor every frame and removes the whole pickup once it
+// is taken.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Pickup {
+    pub sensor: Handle<Node>,
+    // The model that spins and bobs, it must be a child of the pickup.
+    pub model: Handle<Node>,
+    // How fast (in radians per second) the model spins.
+    pub rotation_speed: f32,
+    // Max offset (in meters) of the model when it bobs up and down.
+    pub bob_amplitude: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for Pickup {
+    fn default() -> Self {
+        Self {
+            sensor: Default::default(),
+            model: Default::default(),
+            rotation_speed: 1.5,
+            bob_amplitude: 0.05,
+            time: 0.0,
+        }
+    }
+}
+
+impl Pickup {
+    // Checks whether the given collider touches the sensor of the pickup. Sensors do not push
+    // other bodies away, they only track what is inside of them.
+    pub fn is_touched(&self, graph: &Graph, collider: Handle<Node>) -> bool {
+        graph[self.sensor]
+            .as_collider()
+            .intersects(&graph.physics)
+            .any(|pair| {
+                pair.has_any_active_contact
+                    && (pair.collider1 == collider || pair.collider2 == collider)
+            })
+    }
+
+    // Slowly spins the model and bobs it once per second, so the pickup is easy to notice.
+    fn update(&mut self, graph: &mut Graph, dt: f32) {
+        self.time += dt;
+
+        if self.model.is_some() {
+            graph[self.model]
+                .local_transform_mut()
+                .set_position(Vector3::new(
+                    0.0,
+                    self.bob_amplitude * (self.time * std::f32::consts::TAU).sin(),
+                    0.0,
+                ))
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    self.time * self.rotation_speed,
+                ));
+        }
+    }
+}
+
+// A weapon lying in the world, the player gets the weapon by walking into it.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct WeaponPickup {
+    pub weapon_kind: WeaponKind,
+    pub pickup: Pickup,
+}
+
+impl_component_provider!(WeaponPickup);
+
+impl TypeUuidProvider for WeaponPickup {
+    fn type_uuid() -> Uuid {
+        uuid!("a7c3e1d4-2b58-4f96-8e0a-6d1f93b5c27e")
+    }
+}
+
+impl ScriptTrait for WeaponPickup {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.pickup.update(&mut context.scene.graph, context.dt);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+// Adds rounds to the reserve ammo of a weapon of the given kind, if the player has it.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct AmmoPickup {
+    pub weapon_kind: WeaponKind,
+    pub amount: u32,
+    pub pickup: Pickup,
+}
+
+impl Default for AmmoPickup {
+    fn default() -> Self {
+        Self {
+            weapon_kind: Default::default(),
+            amount: 20,
+            pickup: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(AmmoPickup);
+
+impl TypeUuidProvider for AmmoPickup {
+    fn type_uuid() -> Uuid {
+        uuid!("3d8b5f20-91c7-4e6a-b2d4-7f0e18a6c935")
+    }
+}
+
+impl ScriptTrait for AmmoPickup {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.pickup.update(&mut context.scene.graph, context.dt);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+// Restores some health of the player.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct HealthPickup {
+    pub amount: f32,
+    pub pickup: Pickup,
+}
+
+impl Default for HealthPickup {
+    fn default() -> Self {
+        Self {
+            amount: 25.0,
+            pickup: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(HealthPickup);
+
+impl TypeUuidProvider for HealthPickup {
+    fn type_uuid() -> Uuid {
+        uuid!("c61f0a9e-5d37-4b82-a4e8-2b9d6c71f04a")
+    }
+}
+
+impl ScriptTrait for HealthPickup {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.pickup.update(&mut context.scene.graph, context.dt);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+// What the player gets from a pickup.
+pub enum PickupItem {
+    Weapon(WeaponKind),
+    Ammo {
+        weapon_kind: WeaponKind,
+        amount: u32,
+    },
+    Health(f32),
+}
+
+// Returns every pickup the given collider touches, along with what is inside of it.
+pub fn find_touched_pickups(
+    graph: &Graph,
+    collider: Handle<Node>,
+) -> Vec<(Handle<Node>, PickupItem)> {
+    graph
+        .pair_iter()
+        .filter_map(|(handle, node)| {
+            let (pickup, item) = if let Some(script) = node.try_get_script::<WeaponPickup>() {
+                (&script.pickup, PickupItem::Weapon(script.weapon_kind))
+            } else if let Some(script) = node.try_get_script::<AmmoPickup>() {
+                (
+                    &script.pickup,
+                    PickupItem::Ammo {
+                        weapon_kind: script.weapon_kind,
+                        amount: script.amount,
+                    },
+                )
+            } else if let Some(script) = node.try_get_script::<HealthPickup>() {
+                (&script.pickup, PickupItem::Health(script.amount))
+            } else {
+                return None;
+            };
+
+            if pickup.is_touched(graph, collider) {
+                Some((handle, item))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// Makes a static rigid body with a sensor and the given model at the given position, and
+// assigns a script made by `make_script` to it.
+fn create_pickup<F>(
+    scene: &mut Scene,
+    model: Handle<Node>,
+    position: Vector3<f32>,
+    make_script: F,
+) -> Handle<Node>
+where
+    F: FnOnce(Pickup) -> Script,
+{
+    let sensor = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::ball(0.3))
+        .with_sensor(true)
+        .build(&mut scene.graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[model, sensor])
+            .with_script(make_script(Pickup {
+                sensor,
+                model,
+                ..Default::default()
+            })),
+    )
+    .with_body_type(RigidBodyType::Static)
+    .build(&mut scene.graph)
+}
+
+// There are no models for ammo and health in the tutorial data, so they're just colored boxes.
+fn create_box(graph: &mut Graph, color: Color) -> Handle<Node> {
+    let shape = SurfaceSharedData::new(SurfaceData::make_cube(Matrix4::new_scaling(0.15)));
+
+    let mut material = Material::standard();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(color),
+        )
+        .unwrap();
+
+    MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(shape)
+            .with_material(SharedMaterial::new(material))
+            .build()])
+        .build(graph)
+}
+
+// Creates a pickup with the model of the given weapon kind at the given position.
+pub async fn create_weapon_pickup(
+    scene: &mut Scene,
+    resource_manager: ResourceManager,
+    kind: WeaponKind,
+    position: Vector3<f32>,
+) -> Handle<Node> {
+    // The pickup is still usable without a model, the player just won't see it.
+    let path = kind.model_path();
+    let model = match resource_manager.request_model(asset_path(path)).await {
+        Ok(model) => model.instantiate(scene),
+        Err(err) => {
+            Log::err(format!(
+                "Unable to load weapon model {}! Reason: {:?}",
+                path, err
+            ));
+            PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph)
+        }
+    };
+
+    create_pickup(scene, model, position, |pickup| {
+        Script::new(WeaponPickup {
+            weapon_kind: kind,
+            pickup,
+        })
+    })
+}
+
+pub fn create_ammo_pickup(
+    scene: &mut Scene,
+    weapon_kind: WeaponKind,
+    amount: u32,
+    position: Vector3<f32>,
+) -> Handle<Node> {
+    let model = create_box(&mut scene.graph, Color::opaque(200, 160, 40));
+
+    create_pickup(scene, model, position, |pickup| {
+        Script::new(AmmoPickup {
+            weapon_kind,
+            amount,
+            pickup,
+        })
+    })
+}
+
+pub fn create_health_pickup(
+    scene: &mut Scene,
+    amount: f32,
+    position: Vector3<f32>,
+) -> Handle<Node> {
+    let model = create_box(&mut scene.graph, Color::opaque(40, 200, 60));
+
+    create_pickup(scene, model, position, |pickup| {
+        Script::new(HealthPickup { amount, pickup })
+    })
+}
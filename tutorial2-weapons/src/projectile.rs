@@ -0,0 +1,132 @@
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        color::Color,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    material::{Material, PropertyValue, SharedMaterial},
+    scene::{
+        base::BaseBuilder,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+    },
+};
+
+// Projectile will be destroyed if it haven't hit anything during this time (in seconds).
+const MAX_LIFETIME: f32 = 5.0;
+
+pub struct Projectile {
+    model: Handle<Node>,
+    velocity: Vector3<f32>,
+    damage: f32,
+    lifetime: f32,
+}
+
+impl Projectile {
+    pub fn new(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        velocity: Vector3<f32>,
+        damage: f32,
+    ) -> Self {
+        // Projectile is just a small sphere.
+        let shape = SurfaceSharedData::new(SurfaceData::make_sphere(
+            8,    // Slices
+            8,    // Stacks
+            0.02, // Radius
+            &Matrix4::identity(),
+        ));
+
+        let mut material = Material::standard();
+        material
+            .set_property(
+                &ImmutableString::new("diffuseColor"),
+                PropertyValue::Color(Color::from_rgba(60, 60, 60, 255)),
+            )
+            .unwrap();
+
+        let model = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(shape)
+            .with_material(SharedMaterial::new(material))
+            .build()])
+        .build(graph);
+
+        Self {
+            model,
+            velocity,
+            damage,
+            lifetime: 0.0,
+        }
+    }
+
+    pub fn model(&self) -> Handle<Node> {
+        self.model
+    }
+
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    pub fn damage(&self) -> f32 {
+        self.damage
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lifetime >= MAX_LIFETIME
+    }
+
+    // Moves the projectile and returns the first intersection on its way (if any), intersections
+    // with `ignored_collider` (the shooter) are skipped.
+    pub fn update(
+        &mut self,
+        graph: &mut Graph,
+        dt: f32,
+        ignored_collider: Handle<Node>,
+    ) -> Option<Intersection> {
+        self.lifetime += dt;
+
+        // Projectiles are affected by gravity, this makes them fly by an arc.
+        self.velocity += Vector3::new(0.0, -9.81, 0.0).scale(dt);
+
+        let position = graph[self.model].global_position();
+        let offset = self.velocity.scale(dt);
+
+        // Projectile moves quite fast and could fly through thin objects in a single frame, so
+        // instead of checking its current position, we check the whole path it made during
+        // this frame.
+        let mut intersections = Vec::new();
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(position),
+                ray_direction: offset,
+                max_len: offset.norm(),
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut intersections,
+        );
+
+        graph[self.model]
+            .local_transform_mut()
+            .set_position(position + offset);
+
+        intersections
+            .into_iter()
+            .find(|i| i.collider != ignored_collider)
+    }
+}
@@ -0,0 +1,137 @@
+use fyrox::{
+    core::{algebra::Vector2, pool::Handle},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 80.0;
+
+// What the player has chosen in the dialog.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum QuitAnswer {
+    Yes,
+    No,
+}
+
+// A dialog that asks the player whether they really want to quit, it is shown when the window
+// is about to be closed.
+pub struct QuitDialog {
+    root: Handle<UiNode>,
+    yes: Handle<UiNode>,
+    no: Handle<UiNode>,
+    open: bool,
+}
+
+impl QuitDialog {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let yes;
+        let no;
+        let root = GridBuilder::new(
+            WidgetBuilder::new()
+                // Widgets on the root canvas must have explicit size, otherwise they won't
+                // be visible.
+                .with_width(WIDTH)
+                .with_height(HEIGHT)
+                .with_visibility(false)
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new().on_column(0).on_row(0))
+                        .with_text("Quit?")
+                        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .build(ctx),
+                )
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .with_child({
+                                yes = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_column(0)
+                                        .with_margin(Thickness::uniform(5.0)),
+                                )
+                                .with_text("Yes")
+                                .build(ctx);
+                                yes
+                            })
+                            .with_child({
+                                no = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_column(1)
+                                        .with_margin(Thickness::uniform(5.0)),
+                                )
+                                .with_text("No")
+                                .build(ctx);
+                                no
+                            }),
+                    )
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        Self {
+            root,
+            yes,
+            no,
+            open: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, ui: &UserInterface, open: bool) {
+        self.open = open;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            open,
+        ));
+
+        // Keep the dialog in the center of the window, the window could be resized since the
+        // last time.
+        if open {
+            ui.send_message(WidgetMessage::desired_position(
+                self.root,
+                MessageDirection::ToWidget,
+                (ui.screen_size() - Vector2::new(WIDTH, HEIGHT)).scale(0.5),
+            ));
+        }
+    }
+
+    // Returns the answer if the given message is a click on one of the buttons of the dialog.
+    pub fn answer(&self, message: &UiMessage) -> Option<QuitAnswer> {
+        if !self.open
+            || message.direction() != MessageDirection::FromWidget
+            || !matches!(message.data(), Some(ButtonMessage::Click))
+        {
+            return None;
+        }
+
+        if message.destination() == self.yes {
+            Some(QuitAnswer::Yes)
+        } else if message.destination() == self.no {
+            Some(QuitAnswer::No)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use fyrox::core::color::Color;
+use std::ops::Range;
+
+// What a surface is made of, it defines how a bullet impact on the surface looks like. The kind
+// is set by the tag of a collider (or its rigid body) in the editor.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SurfaceKind {
+    // Anything that is not tagged.
+    Default,
+    Metal,
+    Concrete,
+    Flesh,
+}
+
+// Parameters of the particle system that is shown at the point of a bullet impact.
+pub struct ImpactEffect {
+    pub max_particles: u32,
+    pub size: Range<f32>,
+    // How fast the size of a particle changes.
+    pub size_modifier: Range<f32>,
+    pub lifetime: Range<f32>,
+    // Speed of the particles along the normal of the surface.
+    pub speed: Range<f32>,
+    // Max speed of the particles across the normal of the surface.
+    pub spread: f32,
+    // Vertical acceleration of the particles, negative values make them fall down.
+    pub gravity: f32,
+    // Color of the particles at the start, shortly after the start, shortly before the end
+    // and at the end of their lifetime.
+    pub colors: [Color; 4],
+}
+
+impl SurfaceKind {
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "Metal" => Some(SurfaceKind::Metal),
+            "Concrete" => Some(SurfaceKind::Concrete),
+            "Flesh" => Some(SurfaceKind::Flesh),
+            _ => None,
+        }
+    }
+
+    pub fn impact_effect(self) -> ImpactEffect {
+        match self {
+            // Orange sparks.
+            SurfaceKind::Default => ImpactEffect {
+                max_particles: 200,
+                size: 0.0075..0.015,
+                size_modifier: -0.01..-0.0125,
+                lifetime: 0.05..0.2,
+                speed: 0.025..0.045,
+                spread: 0.0075,
+                gravity: 0.0,
+                colors: [
+                    Color::from_rgba(255, 255, 0, 0),
+                    Color::from_rgba(255, 160, 0, 255),
+                    Color::from_rgba(255, 120, 0, 255),
+                    Color::from_rgba(255, 60, 0, 0),
+                ],
+            },
+            // Bright, fast sparks that fly further and fall down a bit.
+            SurfaceKind::Metal => ImpactEffect {
+                max_particles: 200,
+                size: 0.005..0.01,
+                size_modifier: -0.005..-0.0075,
+                lifetime: 0.1..0.3,
+                speed: 0.03..0.06,
+                spread: 0.015,
+                gravity: -0.1,
+                colors: [
+                    Color::from_rgba(255, 255, 220, 0),
+                    Color::from_rgba(255, 255, 180, 255),
+                    Color::from_rgba(255, 180, 60, 255),
+                    Color::from_rgba(255, 100, 0, 0),
+                ],
+            },
+            // Slow puff of gray dust that grows and settles down.
+            SurfaceKind::Concrete => ImpactEffect {
+                max_particles: 100,
+                size: 0.02..0.04,
+                size_modifier: 0.01..0.02,
+                lifetime: 0.3..0.6,
+                speed: 0.005..0.015,
+                spread: 0.005,
+                gravity: -0.02,
+                colors: [
+                    Color::from_rgba(160, 150, 140, 0),
+                    Color::from_rgba(150, 140, 130, 200),
+                    Color::from_rgba(130, 120, 110, 120),
+                    Color::from_rgba(120, 110, 100, 0),
+                ],
+            },
+            // Dark red droplets that fall down.
+            SurfaceKind::Flesh => ImpactEffect {
+                max_particles: 60,
+                size: 0.01..0.02,
+                size_modifier: -0.01..-0.005,
+                lifetime: 0.2..0.4,
+                speed: 0.01..0.03,
+                spread: 0.01,
+                gravity: -0.1,
+                colors: [
+                    Color::from_rgba(150, 0, 0, 0),
+                    Color::from_rgba(140, 0, 0, 255),
+                    Color::from_rgba(100, 0, 0, 255),
+                    Color::from_rgba(80, 0, 0, 0),
+                ],
+            },
+        }
+    }
+}
@@ -1,58 +1,402 @@
-use fyrox::scene::graph::Graph;
 use fyrox::{
-    core::{algebra::Vector3, math::Vector3Ext, pool::Handle},
+    core::{
+        algebra::Vector3,
+        math::Vector3Ext,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
     engine::resource_manager::ResourceManager,
-    scene::{node::Node, Scene},
+    impl_component_provider,
+    resource::model::Model,
+    scene::{
+        base::BaseBuilder,
+        node::{Node, TypeUuidProvider},
+        pivot::PivotBuilder,
+        sound::{DataSource, SoundBufferResource},
+        Scene,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+    utils::log::Log,
 };
+use tutorial_common::assets::asset_path;
 
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default, Visit, Reflect)]
+pub enum WeaponKind {
+    #[default]
+    Pistol,
+    Rifle,
+    Shotgun,
+    GrenadeLauncher,
+}
+
+impl WeaponKind {
+    pub fn model_path(self) -> &'static str {
+        // There's only one weapon model in the tutorial data, so every kind uses it.
+        "data/models/m4.FBX"
+    }
+
+    // Time (in seconds) between two shots.
+    pub fn shot_interval(self) -> f32 {
+        match self {
+            WeaponKind::Pistol => 0.3,
+            WeaponKind::Rifle => 0.1,
+            WeaponKind::Shotgun => 0.8,
+            WeaponKind::GrenadeLauncher => 1.0,
+        }
+    }
+
+    // Damage of a single bullet (or a pellet).
+    pub fn damage(self) -> f32 {
+        match self {
+            WeaponKind::Pistol => 15.0,
+            WeaponKind::Rifle => 10.0,
+            WeaponKind::Shotgun => 8.0,
+            WeaponKind::GrenadeLauncher => 50.0,
+        }
+    }
+
+    // Amount of bullets (pellets) fired by a single shot.
+    pub fn pellet_count(self) -> usize {
+        match self {
+            WeaponKind::Pistol | WeaponKind::Rifle | WeaponKind::GrenadeLauncher => 1,
+            WeaponKind::Shotgun => 8,
+        }
+    }
+
+    // Half-angle (in degrees) of a cone in which pellets are spread.
+    pub fn spread(self) -> f32 {
+        match self {
+            WeaponKind::Pistol | WeaponKind::Rifle | WeaponKind::GrenadeLauncher => 0.0,
+            WeaponKind::Shotgun => 6.0,
+        }
+    }
+
+    // Initial speed of a projectile, `None` means that the weapon hits instantly (hitscan)
+    // at the point where it is aimed.
+    pub fn projectile_speed(self) -> Option<f32> {
+        match self {
+            WeaponKind::Pistol | WeaponKind::Rifle | WeaponKind::Shotgun => None,
+            WeaponKind::GrenadeLauncher => Some(15.0),
+        }
+    }
+
+    // Automatic weapons keep shooting while the trigger is held.
+    pub fn is_automatic(self) -> bool {
+        self == WeaponKind::Rifle
+    }
+
+    pub fn magazine_size(self) -> u32 {
+        match self {
+            WeaponKind::Pistol => 12,
+            WeaponKind::Rifle => 30,
+            WeaponKind::Shotgun => 8,
+            WeaponKind::GrenadeLauncher => 6,
+        }
+    }
+
+    pub fn reserve_ammo(self) -> u32 {
+        match self {
+            WeaponKind::Pistol => 48,
+            WeaponKind::Rifle => 90,
+            WeaponKind::Shotgun => 32,
+            WeaponKind::GrenadeLauncher => 18,
+        }
+    }
+
+    // Max amount of rounds in the reserve, ammo pickups can't fill it beyond this amount.
+    pub fn max_reserve_ammo(self) -> u32 {
+        match self {
+            WeaponKind::Pistol => 96,
+            WeaponKind::Rifle => 180,
+            WeaponKind::Shotgun => 64,
+            WeaponKind::GrenadeLauncher => 24,
+        }
+    }
+
+    // Angle (in degrees) by which each shot kicks the camera up.
+    pub fn camera_kick(self) -> f32 {
+        match self {
+            WeaponKind::Pistol => 2.0,
+            WeaponKind::Rifle => 1.0,
+            WeaponKind::Shotgun => 4.0,
+            WeaponKind::GrenadeLauncher => 3.0,
+        }
+    }
+
+    // Amount of trauma each shot adds to the camera shake, see `CameraShake`.
+    pub fn shake_trauma(self) -> f32 {
+        match self {
+            WeaponKind::Pistol => 0.15,
+            WeaponKind::Rifle => 0.1,
+            WeaponKind::Shotgun => 0.35,
+            WeaponKind::GrenadeLauncher => 0.25,
+        }
+    }
+}
+
+// The script must be assigned to the root node of a weapon model.
+#[derive(Visit, Reflect, Debug, Clone)]
 pub struct Weapon {
-    model: Handle<Node>,
+    kind: WeaponKind,
     shot_point: Handle<Node>,
+    magazine_size: u32,
+    ammo_in_mag: u32,
+    reserve_ammo: u32,
+    max_reserve_ammo: u32,
+    camera_kick: f32,
+    // Max offset of the weapon when it bobs while the owner moves, zero disables bobbing.
+    pub bob_amplitude: f32,
+    // How many bob cycles (a sway to each side) are made per unit of distance traveled.
+    pub bob_frequency: f32,
+
+    // Runtime state of the weapon is not needed in saved games and in the editor.
+    #[visit(skip)]
+    #[reflect(hidden)]
     shot_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
     recoil_offset: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
     recoil_target_offset: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    reload_timer: f32,
+    // Horizontal speed of the owner, it is set by the owner every frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    owner_speed: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bob_phase: f32,
 }
 
-impl Weapon {
-    pub async fn new(scene: &mut Scene, resource_manager: ResourceManager) -> Self {
-        // Yeah, you need only few lines of code to load a model of any complexity.
-        let model = resource_manager
-            .request_model("data/models/m4.FBX")
-            .await
-            .unwrap()
-            .instantiate(scene);
+impl Default for Weapon {
+    fn default() -> Self {
+        Self::new(WeaponKind::default(), Default::default())
+    }
+}
+
+impl_component_provider!(Weapon);
+
+impl TypeUuidProvider for Weapon {
+    fn type_uuid() -> Uuid {
+        uuid!("5f2e9b17-4c6a-4d83-a0f1-8b3d7e62c954")
+    }
+}
+
+// Time (in seconds) that is needed to reload a weapon.
+const RELOAD_TIME: f32 = 1.5;
+
+// There's no dry fire sound in the tutorial data, so the click is synthesized: a short high
+// tone that fades out in a few milliseconds sounds just like a trigger hitting nothing.
+pub fn create_dry_fire_sound_buffer() -> Option<SoundBufferResource> {
+    const SAMPLE_RATE: usize = 44100;
+    // 20 milliseconds is more than enough, the tone is silent long before that.
+    const LENGTH: usize = SAMPLE_RATE / 50;
+
+    let samples = (0..LENGTH)
+        .map(|i| {
+            let time = i as f32 / SAMPLE_RATE as f32;
+            (std::f32::consts::TAU * 2500.0 * time).sin() * (-time * 400.0).exp()
+        })
+        .collect();
+
+    let buffer = SoundBufferResource::new_generic(DataSource::Raw {
+        sample_rate: SAMPLE_RATE,
+        channel_count: 1,
+        samples,
+    });
+
+    if buffer.is_err() {
+        Log::err("Unable to create dry fire sound, the weapons will click silently!".to_owned());
+    }
 
-        let shot_point = scene.graph.find_by_name(model, "Weapon:ShotPoint");
+    buffer.ok()
+}
+
+// Loads a weapon model of the given kind and assigns the Weapon script to it.
+// Yeah, you need only few lines of code to load a model of any complexity. Returns `None` if
+// the model can't be loaded, the weapon is created without it then.
+pub async fn load_weapon_model(
+    resource_manager: ResourceManager,
+    kind: WeaponKind,
+) -> Option<Model> {
+    let path = kind.model_path();
+    resource_manager
+        .request_model(asset_path(path))
+        .await
+        .map_err(|err| {
+            Log::err(format!(
+                "Unable to load weapon model {}! Reason: {:?}",
+                path, err
+            ))
+        })
+        .ok()
+}
+
+// Creates a weapon from a model that was loaded beforehand, so a weapon can be created in the
+// middle of a frame without waiting for its model. If there's no model, an empty node is used
+// instead, so the game can go on without it.
+pub fn create_weapon(scene: &mut Scene, model: Option<&Model>, kind: WeaponKind) -> Handle<Node> {
+    let model = match model {
+        Some(model) => model.instantiate(scene),
+        None => PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph),
+    };
+
+    // A weapon without a shot point (like the empty one above) shoots from its origin.
+    let mut shot_point = scene.graph.find_by_name(model, "Weapon:ShotPoint");
+    if shot_point.is_none() {
+        shot_point = model;
+    }
+
+    scene.graph[model].set_script(Some(Script::new(Weapon::new(kind, shot_point))));
 
+    model
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind, shot_point: Handle<Node>) -> Self {
         Self {
-            model,
+            kind,
             shot_point,
+            magazine_size: kind.magazine_size(),
+            // Start with full magazine.
+            ammo_in_mag: kind.magazine_size(),
+            reserve_ammo: kind.reserve_ammo(),
+            max_reserve_ammo: kind.max_reserve_ammo(),
+            camera_kick: kind.camera_kick(),
+            bob_amplitude: 0.005,
+            bob_frequency: 1.5,
             shot_timer: 0.0,
             recoil_offset: Default::default(),
             recoil_target_offset: Default::default(),
+            reload_timer: 0.0,
+            owner_speed: 0.0,
+            bob_phase: 0.0,
         }
     }
 
-    pub fn model(&self) -> Handle<Node> {
-        self.model
+    pub fn kind(&self) -> WeaponKind {
+        self.kind
     }
 
     pub fn shot_point(&self) -> Handle<Node> {
         self.shot_point
     }
 
-    pub fn update(&mut self, dt: f32, graph: &mut Graph) {
+    pub fn magazine_size(&self) -> u32 {
+        self.magazine_size
+    }
+
+    pub fn ammo_in_mag(&self) -> u32 {
+        self.ammo_in_mag
+    }
+
+    pub fn reserve_ammo(&self) -> u32 {
+        self.reserve_ammo
+    }
+
+    // Adds rounds to the reserve, but not beyond its max size. Returns how many rounds were
+    // actually added.
+    pub fn add_reserve_ammo(&mut self, amount: u32) -> u32 {
+        let added = amount.min(self.max_reserve_ammo.saturating_sub(self.reserve_ammo));
+        self.reserve_ammo += added;
+        added
+    }
+
+    pub fn set_owner_speed(&mut self, speed: f32) {
+        self.owner_speed = speed;
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_timer > 0.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ammo_in_mag == 0
+    }
+
+    pub fn can_shoot(&self) -> bool {
+        self.shot_timer <= 0.0 && !self.is_empty() && !self.is_reloading()
+    }
+
+    // Returns the angle (in degrees) by which the shot kicks the camera up.
+    pub fn shoot(&mut self) -> f32 {
+        self.shot_timer = self.kind.shot_interval();
+
+        self.ammo_in_mag -= 1;
+
+        self.recoil_target_offset = Vector3::new(0.0, 0.0, -0.025);
+
+        self.camera_kick
+    }
+
+    // Pulling the trigger with empty magazine just "clicks" - no shot is made, but the
+    // trigger needs some time to be pulled again. Returns `true` if the trigger has clicked,
+    // the click must be heard then.
+    pub fn dry_fire(&mut self) -> bool {
+        if self.shot_timer <= 0.0 && !self.is_reloading() {
+            self.shot_timer = 0.5;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn reload(&mut self) {
+        // There's no need to reload a full magazine, and there's nothing to reload with
+        // empty reserve.
+        if !self.is_reloading() && self.ammo_in_mag < self.magazine_size && self.reserve_ammo > 0 {
+            self.reload_timer = RELOAD_TIME;
+        }
+    }
+}
+
+impl ScriptTrait for Weapon {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let dt = context.dt;
+
         self.shot_timer = (self.shot_timer - dt).max(0.0);
 
+        if self.is_reloading() {
+            self.reload_timer -= dt;
+
+            // Move as much rounds from the reserve into the magazine as we can when
+            // reloading is done.
+            if self.reload_timer <= 0.0 {
+                self.reload_timer = 0.0;
+
+                let amount = (self.magazine_size - self.ammo_in_mag).min(self.reserve_ammo);
+                self.ammo_in_mag += amount;
+                self.reserve_ammo -= amount;
+            }
+        }
+
         // `follow` method defined in Vector3Ext trait and it just increases or
         // decreases vector's value in order to "follow" the target value with
         // given speed.
         self.recoil_offset.follow(&self.recoil_target_offset, 0.5);
 
-        // Apply offset to weapon's model.
-        graph[self.model]
+        // Phase of the bob advances with the distance traveled by the owner, so the faster the
+        // owner moves, the faster the weapon bobs.
+        self.bob_phase += self.owner_speed * dt * self.bob_frequency * std::f32::consts::TAU;
+        let bob_amplitude = self.bob_amplitude * self.owner_speed;
+        // Sideways sway is twice slower than up-down movement (absolute value of the sine
+        // repeats twice per its period, so the weapon dips once per sway to each side), this
+        // gives the "figure eight" motion.
+        let bob_offset = Vector3::new(
+            bob_amplitude * self.bob_phase.sin(),
+            -bob_amplitude * self.bob_phase.sin().abs(),
+            0.0,
+        );
+
+        // Apply offset to weapon's model, the script is assigned to it.
+        context.scene.graph[context.handle]
             .local_transform_mut()
-            .set_position(self.recoil_offset);
+            .set_position(self.recoil_offset + bob_offset);
 
         // Check if we've reached target recoil offset.
         if self
@@ -66,13 +410,7 @@ impl Weapon {
         }
     }
 
-    pub fn can_shoot(&self) -> bool {
-        self.shot_timer <= 0.0
-    }
-
-    pub fn shoot(&mut self) {
-        self.shot_timer = 0.1;
-
-        self.recoil_target_offset = Vector3::new(0.0, 0.0, -0.025);
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
     }
 }
@@ -0,0 +1,25 @@
+//! Executor with the game connected to it as a plugin.
+//!
+//! The game can be run without a window (for automated tests or as a dedicated server) by
+//! passing `--headless` argument or by setting `TUTORIAL_HEADLESS` environment variable.
+//! In headless mode the window is hidden and no frames are rendered, but the engine still
+//! creates its graphics context and renderer. Plugins and scripts are updated at the usual
+//! fixed rate, but nobody sends OS events to a game without a window, so their `on_os_event`
+//! is never called: input controllers keep their defaults and the player just stands still.
+use fyrox::engine::executor::Executor;
+use tutorial_common::headless::{is_headless, run_headless};
+use tutorial_shooter::GameConstructor;
+
+const TITLE: &str = "3D Shooter Tutorial";
+
+fn main() {
+    if is_headless() {
+        run_headless(TITLE, GameConstructor);
+        return;
+    }
+
+    let mut executor = Executor::new();
+    executor.get_window().set_title(TITLE);
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}
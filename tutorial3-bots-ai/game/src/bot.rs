@@ -0,0 +1,1356 @@
+use crate::{
+    health_bar::HealthBar,
+    is_game_paused,
+    message::Message,
+    player::Player,
+    weapon::{DamageType, MeleeSweep, Resistances},
+    Game,
+};
+use fyrox::animation::machine::MachineLayer;
+use fyrox::scene::animation::{AnimationPlayer, AnimationPlayerBuilder};
+use fyrox::{
+    animation::{
+        machine::{Machine, Parameter, PoseNode, State, Transition},
+        Animation,
+    },
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        color::Color,
+        futures::executor::block_on,
+        math::SmoothAngle,
+        pool::Handle,
+        reflect::prelude::*,
+        sstorage::ImmutableString,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_component_provider,
+    material::{PropertyValue, SharedMaterial},
+    resource::model::Model,
+    scene::{
+        base::BaseBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::{physics::RayCastOptions, Graph},
+        mesh::Mesh,
+        node::{Node, TypeUuidProvider},
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+    utils::log::Log,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::Sender,
+};
+use tutorial_common::assets::asset_path;
+
+// Time (in seconds) after which a dead bot is removed.
+const DEATH_TIME: f32 = 5.0;
+
+// Time (in seconds) that is needed for a dead bot to fall over, it is used when there's no
+// death animation.
+const FALL_TIME: f32 = 0.6;
+
+// A patrol point is considered reached when a bot is closer to it than this distance.
+const WAYPOINT_REACH_DISTANCE: f32 = 0.2;
+
+// Death animation is optional, the tutorial doesn't ship it.
+const DYING_ANIMATION_PATH: &str = "data/animations/zombie_dying.fbx";
+
+// Set once the death animation has failed to load. It fails the same way for every bot, so
+// there's no need to request it (and warn about it) on every spawn.
+static DYING_ANIMATION_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+// Minimal time (in seconds) between two hits of a bot.
+const ATTACK_COOLDOWN: f32 = 1.0;
+
+// Time (in seconds) between path recalculations, the target moves so the path must be updated.
+const PATH_UPDATE_INTERVAL: f32 = 0.5;
+
+// How strong bots push each other away, chasing must remain dominant so it is less than one.
+const SEPARATION_WEIGHT: f32 = 0.6;
+
+// Time (in seconds) during which a bot flashes after a hit.
+const HIT_FLASH_TIME: f32 = 0.2;
+
+// Color of a bot right after a hit, it then fades back to the original color.
+const HIT_FLASH_COLOR: Color = Color::RED;
+
+// The script must be assigned to a rigid body node, the model of the bot is a child of the body.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Bot {
+    pub collider: Handle<Node>,
+    pub model: Handle<Node>,
+    // How fast (in radians per second) the bot turns.
+    pub turn_speed: f32,
+    // Bots closer than this distance will push each other away, so they won't pile up.
+    pub separation_radius: f32,
+    // Maximum distance at which the bot is able to notice its target.
+    pub vision_range: f32,
+    // Maximum distance at which the bot is able to hear noises.
+    pub hearing_range: f32,
+    // How fast (in meters per second) an alert spreads, the farther the bot is from a noise, the
+    // later it reacts, so nearby bots don't turn all at once. Zero means instant reaction.
+    pub alert_propagation_speed: f32,
+    // Points the bot walks between (in order) when it is not following its target.
+    pub waypoints: Vec<Vector3<f32>>,
+    pub patrol_speed: f32,
+    pub health: f32,
+    // Incoming damage is multiplied by the resistance for its type.
+    pub resistances: Resistances,
+    // The attack of the bot, zombie's hands sweep in front of it.
+    pub melee: MeleeSweep,
+    // The player is able to finish off the bot once its health (relative to the health it has
+    // started with) drops to this value. Zero means the bot can't be finished off.
+    pub finisher_threshold: f32,
+    // Once the health of the bot (relative to the health it has started with) drops to this
+    // value, the bot runs to the rally point to regroup with the others. Zero means the bot
+    // never retreats.
+    pub retreat_threshold: f32,
+    // The bot considers itself regrouped once it and another bot are this close (in meters) to
+    // the rally point.
+    pub rally_radius: f32,
+    // A widget that shows the health of the bot, it is managed by the game.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub health_bar: HealthBar,
+
+    // Runtime state of the bot is not needed in saved games and in the editor.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rigid_body: Handle<Node>,
+    // Health of the bot at the start, it is used to show how much health the bot has left.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    max_health: f32,
+    // The player, it is found in the scene when the bot starts.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    target: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    machine: BotAnimationMachine,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    follow_target: bool,
+    // Current yaw of the bot, it smoothly follows desired direction so the bot turns gradually.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    yaw: SmoothAngle,
+    // A place where the bot heard a noise, it will go there to investigate.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    investigate_position: Option<Vector3<f32>>,
+    // A noise the bot has heard but hasn't reacted to yet, along with the time (in seconds)
+    // left until the reaction.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pending_alert: Option<(Vector3<f32>, f32)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    current_waypoint: usize,
+    // Path to the target on the navigational mesh, it is empty if there's no navmesh.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    path: Vec<Vector3<f32>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    current_path_point: usize,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    path_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    attack_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    dead: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    death_timer: f32,
+    // Rotation of the model at the moment of death, it is used to make the bot fall over.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    model_rotation: UnitQuaternion<f32>,
+    // Materials of the model along with their original diffuse color, they're tinted when the
+    // bot is hit.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    materials: Vec<(SharedMaterial, Color)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hit_flash_timer: f32,
+    // The bot is being finished off, it stands still and doesn't fight back.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stunned: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    retreating: bool,
+    // The bot retreats only once, after that it fights to the end.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    regrouped: bool,
+    // Hits are sent to the game, it applies them to the player.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    sender: Option<Sender<Message>>,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self {
+            collider: Default::default(),
+            model: Default::default(),
+            turn_speed: 4.0,
+            separation_radius: 0.8,
+            vision_range: 1.5,
+            hearing_range: 8.0,
+            alert_propagation_speed: 8.0,
+            waypoints: Default::default(),
+            patrol_speed: 0.5,
+            health: 100.0,
+            resistances: Default::default(),
+            melee: Default::default(),
+            finisher_threshold: 0.25,
+            retreat_threshold: 0.5,
+            rally_radius: 1.5,
+            health_bar: Default::default(),
+            rigid_body: Default::default(),
+            max_health: 100.0,
+            target: Default::default(),
+            machine: Default::default(),
+            follow_target: false,
+            yaw: SmoothAngle {
+                angle: 0.0,
+                target: 0.0,
+                speed: 4.0,
+            },
+            investigate_position: None,
+            pending_alert: None,
+            current_waypoint: 0,
+            path: Default::default(),
+            current_path_point: 0,
+            path_timer: 0.0,
+            attack_timer: 0.0,
+            dead: false,
+            death_timer: 0.0,
+            model_rotation: Default::default(),
+            materials: Default::default(),
+            hit_flash_timer: 0.0,
+            stunned: false,
+            retreating: false,
+            regrouped: false,
+            sender: None,
+        }
+    }
+}
+
+impl_component_provider!(Bot);
+
+impl TypeUuidProvider for Bot {
+    fn type_uuid() -> Uuid {
+        uuid!("3e6f4a52-8c2d-4b71-9d0e-5a7c1f28b6d4")
+    }
+}
+
+pub async fn create_bot(
+    scene: &mut Scene,
+    position: Vector3<f32>,
+    waypoints: Vec<Vector3<f32>>,
+    resource_manager: ResourceManager,
+) -> Handle<Node> {
+    // Load bot 3D model as usual. There's no bot without the model, so the game goes on without
+    // the bot if the model can't be loaded.
+    let model = match resource_manager
+        .request_model(asset_path("data/models/zombie.fbx"))
+        .await
+    {
+        Ok(model) => model.instantiate(scene),
+        Err(err) => {
+            Log::err(format!(
+                "Unable to load data/models/zombie.fbx, the bot won't be created! Reason: {:?}",
+                err
+            ));
+            return Handle::NONE;
+        }
+    };
+
+    scene.graph[model]
+        .local_transform_mut()
+        // Move the model a bit down to make sure bot's feet will be on ground.
+        .set_position(Vector3::new(0.0, -0.45, 0.0))
+        // Scale the model because it is too big.
+        .set_scale(Vector3::new(0.0047, 0.0047, 0.0047));
+
+    let machine = match BotAnimationMachine::new(scene, model, resource_manager).await {
+        Ok(machine) => machine,
+        Err(err) => {
+            Log::err(format!("Unable to create the bot! Reason: {}", err));
+            scene.graph.remove_node(model);
+            return Handle::NONE;
+        }
+    };
+
+    // Add capsule collider for the rigid body.
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::capsule_y(0.25, 0.2))
+        .build(&mut scene.graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(position.x, position.y, position.z))
+                    .build(),
+            )
+            // Attach model and collider to the rigid body.
+            .with_children(&[model, collider])
+            .with_script(Script::new(Bot {
+                collider,
+                model,
+                waypoints,
+                machine,
+                ..Default::default()
+            })),
+    )
+    // We don't want a bot to tilt.
+    .with_locked_rotations(true)
+    .with_can_sleep(false)
+    .build(&mut scene.graph)
+}
+
+impl Bot {
+    pub fn is_dead(&self) -> bool {
+        self.dead
+    }
+
+    pub fn health_ratio(&self) -> f32 {
+        if self.max_health > 0.0 {
+            self.health / self.max_health
+        } else {
+            0.0
+        }
+    }
+
+    // Returns the damage that was actually done, after the resistance was applied.
+    pub fn damage(&mut self, amount: f32, damage_type: DamageType) -> f32 {
+        if self.dead {
+            return 0.0;
+        }
+
+        let amount = amount * self.resistances.multiplier(damage_type);
+        self.health = (self.health - amount).max(0.0);
+        self.hit_flash_timer = HIT_FLASH_TIME;
+
+        amount
+    }
+
+    pub fn can_be_finished(&self) -> bool {
+        !self.dead && self.health > 0.0 && self.health_ratio() <= self.finisher_threshold
+    }
+
+    // Stops the bot while the player is finishing it off, the bot is released if the finisher
+    // is cancelled.
+    pub fn set_stunned(&mut self, stunned: bool) {
+        self.stunned = stunned;
+    }
+
+    // Kills the bot at once, no matter what its resistances are. Returns the health the bot had.
+    pub fn finish(&mut self) -> f32 {
+        let amount = self.health;
+        self.health = 0.0;
+        self.hit_flash_timer = HIT_FLASH_TIME;
+        self.stunned = false;
+        amount
+    }
+
+    // Remembers the materials of the model, so the bot could be tinted on hits. Every instance
+    // of a model shares materials with the model resource (a material is just a shared
+    // reference), so tinting them would tint every bot at once. To prevent this, each surface
+    // gets its own copy of the material first.
+    fn capture_materials(&mut self, graph: &mut Graph) {
+        self.materials.clear();
+
+        if !graph.is_valid_handle(self.model) {
+            return;
+        }
+
+        let nodes = graph.traverse_handle_iter(self.model).collect::<Vec<_>>();
+        for node in nodes {
+            if let Some(mesh) = graph[node].cast_mut::<Mesh>() {
+                for surface in mesh.surfaces_mut() {
+                    let material = SharedMaterial::new(surface.material().lock().clone());
+
+                    // Materials are sets of named properties, which are passed to the shader.
+                    // The standard shader uses `diffuseColor` to tint the diffuse texture.
+                    let color = match material
+                        .lock()
+                        .property_ref(&ImmutableString::new("diffuseColor"))
+                    {
+                        Some(PropertyValue::Color(color)) => *color,
+                        _ => Color::WHITE,
+                    };
+
+                    surface.set_material(material.clone());
+                    self.materials.push((material, color));
+                }
+            }
+        }
+    }
+
+    // Fades the tint of the materials from the flash color back to the original one.
+    fn update_hit_flash(&mut self, dt: f32) {
+        if self.hit_flash_timer <= 0.0 {
+            return;
+        }
+
+        self.hit_flash_timer = (self.hit_flash_timer - dt).max(0.0);
+
+        // The original color is restored exactly once the timer is over.
+        let k = self.hit_flash_timer / HIT_FLASH_TIME;
+        for (material, original_color) in self.materials.iter() {
+            Log::verify(material.lock().set_property(
+                &ImmutableString::new("diffuseColor"),
+                PropertyValue::Color(original_color.lerp(HIT_FLASH_COLOR, k)),
+            ));
+        }
+    }
+
+    // Dead bot is removed by the game after some time, but not earlier than it has finished
+    // playing its death animation.
+    pub fn can_be_removed(&self, scene: &Scene) -> bool {
+        self.dead && self.death_timer <= 0.0 && self.machine.is_death_animation_finished(scene)
+    }
+
+    fn die(&mut self, scene: &mut Scene) {
+        self.dead = true;
+        self.death_timer = DEATH_TIME;
+
+        // Dead body must not be pushed around and must not block anyone, so make the rigid body
+        // kinematic (it won't be affected by any forces) and turn its collider into a sensor.
+        let rigid_body = scene.graph[self.rigid_body].as_rigid_body_mut();
+        rigid_body.set_lin_vel(Default::default());
+        rigid_body.set_body_type(RigidBodyType::KinematicPositionBased);
+
+        // Collider and model are set in the editor for placed bots, they could be missing.
+        if let Some(collider) = scene.graph.try_get_mut(self.collider) {
+            collider.as_collider_mut().set_is_sensor(true);
+        }
+
+        if let Some(model) = scene.graph.try_get(self.model) {
+            self.model_rotation = **model.local_transform().rotation();
+        }
+    }
+
+    // Checks whether there's nothing between the bot and its target, so the bot won't be able
+    // to see the target through walls.
+    fn can_see_target(
+        &self,
+        scene: &Scene,
+        target: Vector3<f32>,
+        target_collider: Handle<Node>,
+    ) -> bool {
+        let self_position = scene.graph[self.rigid_body].global_position();
+        let ray_direction = target - self_position;
+
+        let mut intersections = Vec::new();
+        scene.graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(self_position),
+                ray_direction,
+                max_len: ray_direction.norm(),
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut intersections,
+        );
+
+        // Ignore bot's own capsule, the first thing the ray hits must be the target itself.
+        intersections
+            .iter()
+            .find(|i| i.collider != self.collider)
+            .map_or(true, |i| i.collider == target_collider)
+    }
+
+    // Makes the bot to investigate the noise if it is close enough to hear it. The bot reacts
+    // with a delay that depends on the distance to the noise, see `alert_propagation_speed`.
+    pub fn hear_noise(&mut self, self_position: Vector3<f32>, position: Vector3<f32>) {
+        // Chasing bot is already alerted, retreating one doesn't care.
+        if self.dead || self.follow_target || self.retreating {
+            return;
+        }
+
+        let distance = self_position.metric_distance(&position);
+        if distance > self.hearing_range {
+            return;
+        }
+
+        let delay = if self.alert_propagation_speed > 0.0 {
+            distance / self.alert_propagation_speed
+        } else {
+            0.0
+        };
+
+        // Already alerted bot goes to the latest noise, but it won't react later than it
+        // would to the first one.
+        let delay = self
+            .pending_alert
+            .map_or(delay, |(_, time_left)| time_left.min(delay));
+        self.pending_alert = Some((position, delay));
+    }
+
+    fn update_pending_alert(&mut self, dt: f32) {
+        if let Some((position, time_left)) = self.pending_alert {
+            let time_left = time_left - dt;
+            if time_left > 0.0 {
+                self.pending_alert = Some((position, time_left));
+            } else {
+                self.pending_alert = None;
+
+                // The bot could have noticed its target while the alert was on its way.
+                if !self.follow_target {
+                    self.investigate_position = Some(position);
+                    // Force path recalculation, the bot must go to the noise right away.
+                    self.path_timer = 0.0;
+                }
+            }
+        }
+    }
+
+    // Makes the bot to turn towards given direction (in XZ plane), the actual rotation is
+    // changed gradually in `update`.
+    fn turn_towards(&mut self, direction: Vector3<f32>) {
+        if direction.x != 0.0 || direction.z != 0.0 {
+            self.yaw.set_target(direction.x.atan2(direction.z));
+        }
+    }
+
+    // Turns the bot towards given direction (in XZ plane) and moves it that way.
+    fn walk_towards(&mut self, scene: &mut Scene, direction: Vector3<f32>, speed: f32) {
+        if let Some(direction) = direction.try_normalize(f32::EPSILON) {
+            self.turn_towards(direction);
+
+            let rigid_body = scene.graph[self.rigid_body].as_rigid_body_mut();
+
+            let new_velocity = Vector3::new(
+                direction.x * speed,
+                rigid_body.lin_vel().y,
+                direction.z * speed,
+            );
+
+            rigid_body.set_lin_vel(new_velocity);
+        }
+    }
+
+    // Sums repulsion vectors (in XZ plane) from the bots that are too close, the closer a bot
+    // is, the stronger it pushes.
+    fn separation(&self, position: Vector3<f32>, bot_positions: &[Vector3<f32>]) -> Vector3<f32> {
+        let mut separation = Vector3::default();
+
+        for other_position in bot_positions {
+            let offset = Vector3::new(
+                position.x - other_position.x,
+                0.0,
+                position.z - other_position.z,
+            );
+            let distance = offset.norm();
+
+            // Bots at the very same spot have no direction to push each other in.
+            if distance > f32::EPSILON && distance < self.separation_radius {
+                separation += offset.scale((1.0 - distance / self.separation_radius) / distance);
+            }
+        }
+
+        separation
+    }
+
+    // Returns index of the waypoint which is closest to the given position.
+    fn closest_waypoint(&self, position: Vector3<f32>) -> usize {
+        self.waypoints
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (**a - position)
+                    .norm_squared()
+                    .total_cmp(&(**b - position).norm_squared())
+            })
+            .map_or(0, |(index, _)| index)
+    }
+
+    // Returns the point the bot should move to, in order to reach the target. If the scene has
+    // a navigational mesh, the bot will follow a path on it, so it won't get stuck on walls.
+    // Otherwise the bot moves to the target by a straight line.
+    fn next_path_point(
+        &mut self,
+        scene: &mut Scene,
+        dt: f32,
+        position: Vector3<f32>,
+        target: Vector3<f32>,
+    ) -> Vector3<f32> {
+        let navmesh = match scene.navmeshes.iter_mut().next() {
+            Some(navmesh) => navmesh,
+            None => return target,
+        };
+
+        self.path_timer -= dt;
+        if self.path_timer <= 0.0 {
+            self.path_timer = PATH_UPDATE_INTERVAL;
+
+            self.path.clear();
+            self.current_path_point = 0;
+
+            if let (Some(from), Some(to)) = (
+                navmesh.query_closest(position),
+                navmesh.query_closest(target),
+            ) {
+                if navmesh.build_path(from, to, &mut self.path).is_ok() {
+                    // The path is built from the end to the beginning.
+                    self.path.reverse();
+                    // Navmesh vertices are not exactly at the target, so add the target itself.
+                    self.path.push(target);
+                } else {
+                    self.path.clear();
+                }
+            }
+        }
+
+        // Skip the points that are already reached.
+        while let Some(point) = self.path.get(self.current_path_point) {
+            if Vector3::new(point.x - position.x, 0.0, point.z - position.z).norm()
+                > WAYPOINT_REACH_DISTANCE
+            {
+                return *point;
+            }
+            self.current_path_point += 1;
+        }
+
+        target
+    }
+
+    // Kills the bot once it has run out of health and plays its death, returns `true` if the
+    // bot is dead.
+    fn update_death(&mut self, scene: &mut Scene, dt: f32) -> bool {
+        if !self.dead && self.health <= 0.0 {
+            self.die(scene);
+        }
+
+        if self.dead {
+            // Dead bots do not think, they just play the death animation.
+            self.death_timer -= dt;
+
+            // If there's no death animation, the bot just falls over on its back.
+            if !self.machine.has_death_animation() {
+                let fall_progress = ((DEATH_TIME - self.death_timer) / FALL_TIME).min(1.0);
+                let fall_rotation = UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    -fall_progress * std::f32::consts::FRAC_PI_2,
+                );
+
+                if let Some(model) = scene.graph.try_get_mut(self.model) {
+                    model
+                        .local_transform_mut()
+                        .set_rotation(fall_rotation * self.model_rotation);
+                }
+            }
+
+            let input = BotAnimationMachineInput {
+                walk: false,
+                attack: false,
+                dying: true,
+            };
+
+            self.machine.update(scene, dt, input);
+        }
+
+        self.dead
+    }
+
+    fn update(
+        &mut self,
+        scene: &mut Scene,
+        dt: f32,
+        target: Vector3<f32>,
+        target_collider: Handle<Node>,
+        bot_positions: &[Vector3<f32>],
+        rally_point: Option<Vector3<f32>>,
+    ) {
+        let attack_distance = 0.6;
+
+        self.update_pending_alert(dt);
+
+        // Simple AI - follow target by a straight line.
+        let self_position = scene.graph[self.rigid_body].global_position();
+        let direction = target - self_position;
+
+        // Distance to target.
+        let distance = direction.norm();
+
+        // Badly hurt bot stops whatever it does and runs to the rally point.
+        if !self.retreating
+            && !self.regrouped
+            && rally_point.is_some()
+            && self.health_ratio() <= self.retreat_threshold
+        {
+            self.retreating = true;
+            self.follow_target = false;
+            self.investigate_position = None;
+            self.pending_alert = None;
+            // Force path recalculation, the bot must run away right now.
+            self.path_timer = 0.0;
+        }
+
+        // Retreating bot still fights back if the target is right next to it, there's no way
+        // to run away from it.
+        let retreat_point = if self.retreating && distance >= attack_distance {
+            rally_point
+        } else {
+            None
+        };
+
+        if self.follow_target {
+            // Stop chasing once the target is hidden behind something and go back to patrol
+            // starting from the closest waypoint.
+            if !self.can_see_target(scene, target, target_collider) {
+                self.follow_target = false;
+                self.current_waypoint = self.closest_waypoint(self_position);
+            }
+        } else if !self.retreating
+            && distance != 0.0
+            && distance < self.vision_range
+            && self.can_see_target(scene, target, target_collider)
+        {
+            self.follow_target = true;
+            self.investigate_position = None;
+        }
+
+        let mut walk = false;
+
+        if let Some(rally_point) = retreat_point {
+            let to_rally_point = Vector3::new(
+                rally_point.x - self_position.x,
+                0.0,
+                rally_point.z - self_position.z,
+            );
+
+            if to_rally_point.norm() < self.rally_radius {
+                // The bot waits for the others at the rally point. Once somebody has come, they
+                // attack together: the bots go to where the target is. The bot also attacks if
+                // the target has found it there, it is cornered.
+                let is_regrouped = bot_positions.iter().any(|position| {
+                    Vector3::new(rally_point.x - position.x, 0.0, rally_point.z - position.z).norm()
+                        < self.rally_radius
+                });
+                let is_cornered = distance < self.vision_range
+                    && self.can_see_target(scene, target, target_collider);
+
+                if is_regrouped || is_cornered {
+                    self.retreating = false;
+                    self.regrouped = true;
+                    self.investigate_position = Some(target);
+                    self.path_timer = 0.0;
+                }
+
+                let rigid_body = scene.graph[self.rigid_body].as_rigid_body_mut();
+                rigid_body.set_lin_vel(Vector3::new(0.0, rigid_body.lin_vel().y, 0.0));
+            } else {
+                let next_point = self.next_path_point(scene, dt, self_position, rally_point);
+                let to_point = Vector3::new(
+                    next_point.x - self_position.x,
+                    0.0,
+                    next_point.z - self_position.z,
+                );
+
+                // Run as fast as when chasing.
+                self.walk_towards(scene, to_point, 0.9);
+
+                walk = true;
+            }
+        } else if (self.follow_target || self.retreating) && distance != 0.0 {
+            let next_point = self.next_path_point(scene, dt, self_position, target);
+            let chase_direction = Vector3::new(
+                next_point.x - self_position.x,
+                0.0,
+                next_point.z - self_position.z,
+            )
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+
+            // Let nearby bots spread out a bit, so they won't all converge on the same point.
+            let separation = self
+                .separation(self_position, bot_positions)
+                .scale(SEPARATION_WEIGHT);
+            let move_direction = (chase_direction + separation)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default();
+
+            // Make sure bot is facing towards the direction it moves, or towards the target if
+            // it is close enough to attack.
+            let look_direction = if distance > attack_distance && move_direction != Vector3::zeros()
+            {
+                move_direction
+            } else {
+                Vector3::new(direction.x, 0.0, direction.z)
+            };
+            self.turn_towards(look_direction);
+
+            let rigid_body = scene.graph[self.rigid_body].as_rigid_body_mut();
+
+            // Move only if we're far enough from the target.
+            if distance > attack_distance {
+                // Scale the direction vector by movement speed.
+                let xz_velocity = move_direction.scale(0.9);
+
+                let new_velocity =
+                    Vector3::new(xz_velocity.x, rigid_body.lin_vel().y, xz_velocity.z);
+
+                rigid_body.set_lin_vel(new_velocity);
+
+                walk = true;
+            }
+        } else if let Some(noise_position) = self.investigate_position {
+            // Investigate - walk to the place where the noise came from.
+            let noise_distance = Vector3::new(
+                noise_position.x - self_position.x,
+                0.0,
+                noise_position.z - self_position.z,
+            )
+            .norm();
+
+            if noise_distance < WAYPOINT_REACH_DISTANCE {
+                // The bot has arrived, start chasing if the target is in sight, otherwise get
+                // back to patrol.
+                self.investigate_position = None;
+                if self.can_see_target(scene, target, target_collider) {
+                    self.follow_target = true;
+                } else {
+                    self.current_waypoint = self.closest_waypoint(self_position);
+                }
+            } else {
+                let next_point = self.next_path_point(scene, dt, self_position, noise_position);
+                let to_point = Vector3::new(
+                    next_point.x - self_position.x,
+                    0.0,
+                    next_point.z - self_position.z,
+                );
+
+                self.walk_towards(scene, to_point, self.patrol_speed);
+
+                walk = true;
+            }
+        } else if let Some(waypoint) = self.waypoints.get(self.current_waypoint).cloned() {
+            // Patrol - walk between waypoints in order.
+            let to_waypoint = Vector3::new(
+                waypoint.x - self_position.x,
+                0.0,
+                waypoint.z - self_position.z,
+            );
+
+            if to_waypoint.norm() < WAYPOINT_REACH_DISTANCE {
+                // Switch to the next waypoint, the bot will turn to it on next frame.
+                self.current_waypoint = (self.current_waypoint + 1) % self.waypoints.len();
+            } else {
+                self.walk_towards(scene, to_waypoint, self.patrol_speed);
+
+                walk = true;
+            }
+        }
+
+        // Turn gradually, the rotation is around vertical axis only so the bot stays upright.
+        self.yaw.speed = self.turn_speed;
+        self.yaw.update(dt);
+        scene.graph[self.rigid_body]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                self.yaw.angle(),
+            ));
+
+        let attack = distance < attack_distance;
+
+        let input = BotAnimationMachineInput {
+            walk,
+            attack,
+            dying: false,
+        };
+
+        self.machine.update(scene, dt, input);
+
+        // Deal damage only if the target is in the way of zombie's hands while they sweep, but
+        // not more often than the cooldown allows.
+        self.attack_timer -= dt;
+        match self.machine.attack_time(scene) {
+            Some(time) => {
+                let forward = scene.graph[self.rigid_body].look_vector();
+                let target_in_arc = self.melee.is_in_arc(self_position, forward, target);
+
+                if self.melee.update(time, target_in_arc) && self.attack_timer <= 0.0 {
+                    if let Some(sender) = self.sender.as_ref() {
+                        Log::verify(sender.send(Message::DamagePlayer {
+                            amount: self.melee.damage,
+                        }));
+                    }
+
+                    self.attack_timer = ATTACK_COOLDOWN;
+                }
+            }
+            None => self.melee.reset(),
+        }
+    }
+}
+
+impl ScriptTrait for Bot {
+    fn on_init(&mut self, context: &mut ScriptContext) {
+        self.rigid_body = context.handle;
+        self.max_health = self.health;
+
+        // The animation machine is not saved, so bots that were placed in the editor or loaded
+        // from a saved game don't have one. Build it for the model of the bot, animation player
+        // of the previous machine (if the bot was saved with one) is useless without it.
+        let graph = &mut context.scene.graph;
+        if !self.machine.is_valid(graph) && graph.is_valid_handle(self.model) {
+            let old_players = graph[self.model]
+                .children()
+                .iter()
+                .filter(|child| {
+                    graph[**child]
+                        .query_component_ref::<AnimationPlayer>()
+                        .is_some()
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            for old_player in old_players {
+                graph.remove_node(old_player);
+            }
+
+            // The bot still works without animations, its model just won't move.
+            match block_on(BotAnimationMachine::new(
+                context.scene,
+                self.model,
+                context.resource_manager.clone(),
+            )) {
+                Ok(machine) => self.machine = machine,
+                Err(err) => Log::err(format!(
+                    "Unable to create animations of the bot! Reason: {}",
+                    err
+                )),
+            }
+        }
+
+        self.capture_materials(&mut context.scene.graph);
+        self.sender = context
+            .plugins
+            .iter()
+            .find_map(|plugin| plugin.cast::<Game>())
+            .map(|game| game.message_sender());
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if is_game_paused(context.plugins) {
+            return;
+        }
+
+        let scene = &mut context.scene;
+
+        self.update_hit_flash(context.dt);
+
+        // Dead bots don't need the player, they just lie there until the game removes them.
+        if self.update_death(scene, context.dt) {
+            return;
+        }
+
+        if self.stunned {
+            let rigid_body = scene.graph[self.rigid_body].as_rigid_body_mut();
+            rigid_body.set_lin_vel(Vector3::new(0.0, rigid_body.lin_vel().y, 0.0));
+
+            let input = BotAnimationMachineInput {
+                walk: false,
+                attack: false,
+                dying: false,
+            };
+
+            self.machine.update(scene, context.dt, input);
+            self.melee.reset();
+
+            return;
+        }
+
+        // Find the player, there's only one in the scene.
+        if !scene.graph.is_valid_handle(self.target) {
+            self.target = scene
+                .graph
+                .pair_iter()
+                .find(|(_, node)| node.try_get_script::<Player>().is_some())
+                .map_or(Handle::NONE, |(handle, _)| handle);
+        }
+
+        let (target, target_collider) = match scene
+            .graph
+            .try_get(self.target)
+            .and_then(|node| Some((node.global_position(), node.try_get_script::<Player>()?)))
+        {
+            Some((position, player)) => (position, player.collider),
+            // Nobody to hunt.
+            None => return,
+        };
+
+        // Each bot must know where the others are, so they could keep distance from each other.
+        // The bot itself is not in the list.
+        let self_handle = context.handle;
+        let bot_positions = scene
+            .graph
+            .pair_iter()
+            .filter(|(handle, node)| {
+                *handle != self_handle
+                    && node
+                        .try_get_script::<Bot>()
+                        .map_or(false, |bot| !bot.is_dead())
+            })
+            .map(|(_, node)| node.global_position())
+            .collect::<Vec<_>>();
+
+        // Hurt bots regroup at the point that is chosen by the game.
+        let rally_point = context
+            .plugins
+            .iter()
+            .find_map(|plugin| plugin.cast::<Game>())
+            .and_then(|game| game.rally_point());
+
+        self.update(
+            scene,
+            context.dt,
+            target,
+            target_collider,
+            &bot_positions,
+            rally_point,
+        );
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+// Simple helper method to create a state supplied with PlayAnimation node.
+fn create_play_animation_state(
+    animation_resource: Model,
+    name: &str,
+    layer: &mut MachineLayer,
+    scene: &mut Scene,
+    model: Handle<Node>,
+) -> Result<(Handle<Animation>, Handle<State>), String> {
+    // Animations retargetting just makes an instance of animation and binds it to
+    // given model using names of bones.
+    let animation = *animation_resource
+        .retarget_animations(model, &mut scene.graph)
+        .first()
+        .ok_or_else(|| format!("There's no animation for the {} state!", name))?;
+    // Create new PlayAnimation node and add it to machine.
+    let node = layer.add_node(PoseNode::make_play_animation(animation));
+    // Make a state using the node we've made.
+    let state = layer.add_state(State::new(name, node));
+    Ok((animation, state))
+}
+
+pub struct BotAnimationMachineInput {
+    // Whether a bot is walking or not.
+    pub walk: bool,
+    // Whether a bot is attacking or not.
+    pub attack: bool,
+    // Whether a bot is dying or not.
+    pub dying: bool,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct BotAnimationMachine {
+    animation_player: Handle<Node>,
+    machine: Machine,
+    attack_animation: Handle<Animation>,
+    attack_state: Handle<State>,
+    die_animation: Handle<Animation>,
+}
+
+impl BotAnimationMachine {
+    // Names of parameters that will be used for transition rules in machine.
+    const IDLE_TO_WALK: &'static str = "IdleToWalk";
+    const WALK_TO_IDLE: &'static str = "WalkToIdle";
+    const WALK_TO_ATTACK: &'static str = "WalkToAttack";
+    const IDLE_TO_ATTACK: &'static str = "IdleToAttack";
+    const ATTACK_TO_IDLE: &'static str = "AttackToIdle";
+    const ATTACK_TO_WALK: &'static str = "AttackToWalk";
+    const DYING: &'static str = "Dying";
+
+    pub async fn new(
+        scene: &mut Scene,
+        model: Handle<Node>,
+        resource_manager: ResourceManager,
+    ) -> Result<Self, String> {
+        // Load animations in parallel.
+        let (
+            walk_animation_resource,
+            idle_animation_resource,
+            attack_animation_resource,
+            dying_animation_resource,
+        ) = fyrox::core::futures::join!(
+            resource_manager.request_model(asset_path("data/animations/zombie_walk.fbx")),
+            resource_manager.request_model(asset_path("data/animations/zombie_idle.fbx")),
+            resource_manager.request_model(asset_path("data/animations/zombie_attack.fbx")),
+            async {
+                if DYING_ANIMATION_UNAVAILABLE.load(Ordering::Relaxed) {
+                    Err("it has failed to load before".to_owned())
+                } else {
+                    resource_manager
+                        .request_model(asset_path(DYING_ANIMATION_PATH))
+                        .await
+                        .map_err(|err| format!("{:?}", err))
+                }
+            },
+        );
+
+        // Idle, walk and attack animations are required, there's no machine without them.
+        let load_error = |path: &str, err| format!("Unable to load {}! Reason: {:?}", path, err);
+        let walk_animation_resource = walk_animation_resource
+            .map_err(|err| load_error("data/animations/zombie_walk.fbx", err))?;
+        let idle_animation_resource = idle_animation_resource
+            .map_err(|err| load_error("data/animations/zombie_idle.fbx", err))?;
+        let attack_animation_resource = attack_animation_resource
+            .map_err(|err| load_error("data/animations/zombie_attack.fbx", err))?;
+
+        let animation_player =
+            AnimationPlayerBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        scene.graph.link_nodes(animation_player, model);
+
+        let mut machine = Machine::new();
+
+        let root = machine.layers_mut().first_mut().unwrap();
+
+        // Now create three states with different animations.
+        let states =
+            create_play_animation_state(idle_animation_resource, "Idle", root, scene, model)
+                .and_then(|idle| {
+                    let walk = create_play_animation_state(
+                        walk_animation_resource,
+                        "Walk",
+                        root,
+                        scene,
+                        model,
+                    )?;
+                    let attack = create_play_animation_state(
+                        attack_animation_resource,
+                        "Attack",
+                        root,
+                        scene,
+                        model,
+                    )?;
+                    Ok((idle, walk, attack))
+                });
+
+        let ((_, idle_state), (walk_animation, walk_state), (attack_animation, attack_state)) =
+            match states {
+                Ok(states) => states,
+                Err(err) => {
+                    // Retargeted animations are stored in the animation player, so they're
+                    // removed along with it.
+                    scene.graph.remove_node(animation_player);
+                    return Err(err);
+                }
+            };
+
+        // Next, define transitions between states.
+        root.add_transition(Transition::new(
+            // A name for debugging.
+            "Idle->Walk",
+            // Source state.
+            idle_state,
+            // Target state.
+            walk_state,
+            // Transition time in seconds.
+            0.4,
+            // A name of transition rule parameter.
+            Self::IDLE_TO_WALK,
+        ));
+        root.add_transition(Transition::new(
+            "Walk->Idle",
+            walk_state,
+            idle_state,
+            0.4,
+            Self::WALK_TO_IDLE,
+        ));
+        root.add_transition(Transition::new(
+            "Walk->Attack",
+            walk_state,
+            attack_state,
+            0.4,
+            Self::WALK_TO_ATTACK,
+        ));
+        root.add_transition(Transition::new(
+            "Idle->Attack",
+            idle_state,
+            attack_state,
+            0.4,
+            Self::IDLE_TO_ATTACK,
+        ));
+        root.add_transition(Transition::new(
+            "Attack->Idle",
+            attack_state,
+            idle_state,
+            0.4,
+            Self::ATTACK_TO_IDLE,
+        ));
+        root.add_transition(Transition::new(
+            "Attack->Walk",
+            attack_state,
+            walk_state,
+            0.4,
+            Self::ATTACK_TO_WALK,
+        ));
+
+        // Death animation is optional, it is not shipped with the tutorial. You can get one from
+        // Mixamo: pick the same "zombie" character, find "Zombie Dying" animation and download
+        // it as FBX "Without Skin", then put it into data/animations/zombie_dying.fbx. The
+        // animation must be made for the same skeleton, because it is retargeted by bone names.
+        // If there's no such animation, dead bots will just fall over.
+        let die_state = dying_animation_resource.and_then(|dying_animation_resource| {
+            create_play_animation_state(dying_animation_resource, "Die", root, scene, model)
+        });
+        let die_animation = match die_state {
+            Ok((die_animation, die_state)) => {
+                // A bot can die in any state.
+                root.add_transition(Transition::new(
+                    "Idle->Die",
+                    idle_state,
+                    die_state,
+                    0.2,
+                    Self::DYING,
+                ));
+                root.add_transition(Transition::new(
+                    "Walk->Die",
+                    walk_state,
+                    die_state,
+                    0.2,
+                    Self::DYING,
+                ));
+                root.add_transition(Transition::new(
+                    "Attack->Die",
+                    attack_state,
+                    die_state,
+                    0.2,
+                    Self::DYING,
+                ));
+
+                // The bot dies only once, so the animation must not be looped.
+                if let Some(animation_player) =
+                    scene.graph[animation_player].query_component_mut::<AnimationPlayer>()
+                {
+                    animation_player.animations_mut()[die_animation].set_loop(false);
+                }
+
+                die_animation
+            }
+            Err(err) => {
+                // Warn only for the first bot, the rest won't have the animation either.
+                if !DYING_ANIMATION_UNAVAILABLE.swap(true, Ordering::Relaxed) {
+                    Log::warn(format!(
+                        "Unable to load {}, bots won't play death animation! Reason: {}",
+                        DYING_ANIMATION_PATH, err
+                    ));
+                }
+
+                Default::default()
+            }
+        };
+
+        // Define entry state.
+        root.set_entry_state(idle_state);
+
+        Ok(Self {
+            animation_player,
+            machine,
+            attack_animation,
+            attack_state,
+            die_animation,
+        })
+    }
+
+    pub fn is_valid(&self, graph: &Graph) -> bool {
+        graph.is_valid_handle(self.animation_player)
+    }
+
+    pub fn has_death_animation(&self) -> bool {
+        self.die_animation.is_some()
+    }
+
+    pub fn is_death_animation_finished(&self, scene: &Scene) -> bool {
+        if self.die_animation.is_none() {
+            return true;
+        }
+
+        scene
+            .graph
+            .try_get(self.animation_player)
+            .and_then(|node| node.query_component_ref::<AnimationPlayer>())
+            .map_or(true, |animation_player| {
+                animation_player.animations()[self.die_animation].has_ended()
+            })
+    }
+
+    // Returns time position of the attack animation, if the bot is attacking right now.
+    pub fn attack_time(&self, scene: &Scene) -> Option<f32> {
+        let is_attacking = self
+            .machine
+            .layers()
+            .first()
+            .map_or(false, |layer| layer.active_state() == self.attack_state);
+
+        if !is_attacking {
+            return None;
+        }
+
+        Some(
+            scene
+                .graph
+                .try_get(self.animation_player)?
+                .query_component_ref::<AnimationPlayer>()?
+                .animations()[self.attack_animation]
+                .time_position(),
+        )
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, dt: f32, input: BotAnimationMachineInput) {
+        // Nothing to animate, the bot has no model or its model was removed.
+        let animation_player = match scene
+            .graph
+            .try_get(self.animation_player)
+            .and_then(|node| node.query_component_ref::<AnimationPlayer>())
+        {
+            Some(animation_player) => animation_player,
+            None => return,
+        };
+
+        self.machine
+            // Set transition parameters, dying bot must not switch to any other state.
+            .set_parameter(
+                Self::WALK_TO_IDLE,
+                Parameter::Rule(!input.walk && !input.dying),
+            )
+            .set_parameter(Self::IDLE_TO_WALK, Parameter::Rule(input.walk))
+            .set_parameter(Self::WALK_TO_ATTACK, Parameter::Rule(input.attack))
+            .set_parameter(Self::IDLE_TO_ATTACK, Parameter::Rule(input.attack))
+            .set_parameter(
+                Self::ATTACK_TO_IDLE,
+                Parameter::Rule(!input.attack && !input.dying),
+            )
+            .set_parameter(
+                Self::ATTACK_TO_WALK,
+                Parameter::Rule(!input.attack && !input.dying),
+            )
+            .set_parameter(Self::DYING, Parameter::Rule(input.dying))
+            // Update machine and evaluate final pose.
+            .evaluate_pose(animation_player.animations(), dt)
+            // Apply the pose to the graph.
+            .apply(&mut scene.graph);
+    }
+}
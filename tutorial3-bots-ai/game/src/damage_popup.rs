@@ -0,0 +1,105 @@
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    gui::{
+        brush::Brush,
+        message::MessageDirection,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, UiNode, UserInterface,
+    },
+};
+
+// How long (in seconds) a popup stays on the screen.
+const LIFETIME: f32 = 0.8;
+// How far (in pixels) a popup rises during its lifetime.
+const RISE_HEIGHT: f32 = 40.0;
+
+const WIDTH: f32 = 60.0;
+const HEIGHT: f32 = 20.0;
+
+// A number that pops up at the place of a hit, rises and fades out.
+pub struct DamagePopup {
+    // The text is created by the game on the next update, scripts have no access to the UI.
+    text: Handle<UiNode>,
+    // Popup follows the point of the hit in the world, so it won't slide when the camera turns.
+    position: Vector3<f32>,
+    amount: f32,
+    age: f32,
+}
+
+impl DamagePopup {
+    pub fn new(position: Vector3<f32>, amount: f32) -> Self {
+        Self {
+            text: Default::default(),
+            position,
+            amount,
+            age: 0.0,
+        }
+    }
+
+    pub fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age >= LIFETIME
+    }
+
+    // Advances the popup and moves it to the given screen position of its point of the world,
+    // the popup is hidden if there's no position (the point is not in front of the camera).
+    pub fn update(
+        &mut self,
+        ui: &mut UserInterface,
+        dt: f32,
+        screen_position: Option<Vector2<f32>>,
+    ) {
+        if self.text.is_none() {
+            self.text = TextBuilder::new(
+                WidgetBuilder::new()
+                    // Widgets on the root canvas must have explicit size, otherwise they won't
+                    // be visible.
+                    .with_width(WIDTH)
+                    .with_height(HEIGHT)
+                    .with_visibility(false),
+            )
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .with_text(format!("{:.0}", self.amount))
+            .build(&mut ui.build_ctx());
+        }
+
+        self.age += dt;
+
+        let k = (self.age / LIFETIME).min(1.0);
+
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            screen_position.is_some(),
+        ));
+
+        if let Some(screen_position) = screen_position {
+            ui.send_message(WidgetMessage::desired_position(
+                self.text,
+                MessageDirection::ToWidget,
+                screen_position - Vector2::new(WIDTH * 0.5, HEIGHT + RISE_HEIGHT * k),
+            ));
+
+            ui.send_message(WidgetMessage::foreground(
+                self.text,
+                MessageDirection::ToWidget,
+                Brush::Solid(Color::from_rgba(255, 220, 0, ((1.0 - k) * 255.0) as u8)),
+            ));
+        }
+    }
+
+    pub fn remove(&self, ui: &UserInterface) {
+        // The text might not be created yet.
+        if self.text.is_some() {
+            ui.send_message(WidgetMessage::remove(self.text, MessageDirection::ToWidget));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 80.0;
+
+// A screen that is shown when the player dies, it lets the player to restart the game.
+pub struct DeathScreen {
+    root: Handle<UiNode>,
+    restart_button: Handle<UiNode>,
+    visible: bool,
+}
+
+impl DeathScreen {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let restart_button = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .with_height(30.0)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .with_text("Restart")
+        .build(ctx);
+
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                // Widgets on the root canvas must have explicit size, otherwise they won't
+                // be visible.
+                .with_width(WIDTH)
+                .with_height(HEIGHT)
+                .with_visibility(false)
+                .with_child(
+                    TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_height(40.0)
+                            .with_foreground(Brush::Solid(Color::RED)),
+                    )
+                    .with_text("You Died")
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx),
+                )
+                .with_child(restart_button),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            restart_button,
+            visible: false,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visibility(&mut self, ui: &UserInterface, visible: bool) {
+        self.visible = visible;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+
+        // Keep the screen in the center of the window, the window could be resized since the
+        // last time.
+        if visible {
+            ui.send_message(WidgetMessage::desired_position(
+                self.root,
+                MessageDirection::ToWidget,
+                (ui.screen_size() - Vector2::new(WIDTH, HEIGHT)).scale(0.5),
+            ));
+        }
+    }
+
+    pub fn is_restart_requested(&self, message: &UiMessage) -> bool {
+        message.destination() == self.restart_button
+            && message.direction() == MessageDirection::FromWidget
+            && matches!(message.data(), Some(ButtonMessage::Click))
+    }
+}
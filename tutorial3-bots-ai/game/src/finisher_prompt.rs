@@ -0,0 +1,71 @@
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        brush::Brush,
+        message::MessageDirection,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, UiNode, UserInterface,
+    },
+};
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 30.0;
+
+// A hint below the center of the screen, it is shown while there's a weak bot the player is able
+// to finish off.
+pub struct FinisherPrompt {
+    text: Handle<UiNode>,
+    visible: bool,
+}
+
+impl FinisherPrompt {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                // Widgets on the root canvas must have explicit size, otherwise they won't
+                // be visible.
+                .with_width(WIDTH)
+                .with_height(HEIGHT)
+                .with_visibility(false)
+                .with_foreground(Brush::Solid(Color::opaque(255, 220, 0))),
+        )
+        .with_text("Press F to finish")
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .build(&mut ui.build_ctx());
+
+        Self {
+            text,
+            visible: false,
+        }
+    }
+
+    pub fn set_visibility(&mut self, ui: &UserInterface, visible: bool) {
+        // The prompt is updated every frame, so there's no need to flood the UI with the same
+        // messages.
+        if self.visible == visible {
+            return;
+        }
+
+        self.visible = visible;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+
+        // Keep the prompt below the crosshair, the window could be resized since the last time.
+        if visible {
+            let screen_size = ui.screen_size();
+            ui.send_message(WidgetMessage::desired_position(
+                self.text,
+                MessageDirection::ToWidget,
+                Vector2::new(
+                    (screen_size.x - WIDTH) * 0.5,
+                    screen_size.y * 0.5 + HEIGHT * 2.0,
+                ),
+            ));
+        }
+    }
+}
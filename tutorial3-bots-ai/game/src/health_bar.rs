@@ -0,0 +1,96 @@
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface,
+    },
+};
+
+const WIDTH: f32 = 50.0;
+const HEIGHT: f32 = 6.0;
+
+// A bar that floats over a bot and shows how much health it has left. It is an ordinary
+// widget on the root canvas, which is moved to the screen position of the bot every frame.
+#[derive(Default, Debug, Clone)]
+pub struct HealthBar {
+    root: Handle<UiNode>,
+    fill: Handle<UiNode>,
+}
+
+impl HealthBar {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let fill = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(WIDTH)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_background(Brush::Solid(Color::RED)),
+        )
+        .with_stroke_thickness(Thickness::zero())
+        .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                // Widgets on the root canvas must have explicit size, otherwise they won't
+                // be visible.
+                .with_width(WIDTH)
+                .with_height(HEIGHT)
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::opaque(40, 40, 40)))
+                .with_child(fill),
+        )
+        .with_stroke_thickness(Thickness::zero())
+        .build(ctx);
+
+        Self { root, fill }
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.root.is_some()
+    }
+
+    // Centers the bar at the given screen position and fills it according to the health
+    // ratio (in [0; 1] range). The bar is hidden when there's no position (the point is not
+    // in front of the camera) or when the health is full.
+    pub fn update(&self, ui: &UserInterface, position: Option<Vector2<f32>>, health_ratio: f32) {
+        let screen_size = ui.screen_size();
+
+        let position = position.filter(|p| {
+            health_ratio < 1.0
+                && p.x >= 0.0
+                && p.y >= 0.0
+                && p.x <= screen_size.x
+                && p.y <= screen_size.y
+        });
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            position.is_some(),
+        ));
+
+        if let Some(position) = position {
+            ui.send_message(WidgetMessage::desired_position(
+                self.root,
+                MessageDirection::ToWidget,
+                position - Vector2::new(WIDTH * 0.5, HEIGHT),
+            ));
+
+            ui.send_message(WidgetMessage::width(
+                self.fill,
+                MessageDirection::ToWidget,
+                WIDTH * health_ratio.clamp(0.0, 1.0),
+            ));
+        }
+    }
+
+    pub fn remove(&mut self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::remove(self.root, MessageDirection::ToWidget));
+
+        *self = Default::default();
+    }
+}
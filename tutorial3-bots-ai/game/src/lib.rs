@@ -0,0 +1,667 @@
+use crate::{
+    bot::{create_bot, Bot},
+    damage_popup::DamagePopup,
+    death_screen::DeathScreen,
+    finisher_prompt::FinisherPrompt,
+    health_bar::HealthBar,
+    message::Message,
+    player::Player,
+    rng::{initial_seed, XorShiftRng},
+    weapon::{create_weapon, Weapon},
+};
+use fyrox::{
+    core::{algebra::Vector3, futures::executor::block_on, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::message::UiMessage,
+    plugin::{Plugin, PluginConstructor, PluginContext, PluginRegistrationContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        loader::AsyncSceneLoader,
+        node::Node,
+        pivot::PivotBuilder,
+        rigidbody::RigidBodyBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+    utils::log::Log,
+};
+use std::sync::mpsc::{self, Receiver, Sender};
+use tutorial_common::{
+    assets::asset_path, fps::FpsCounter, free_camera::FreeCamera, fullscreen::FullscreenToggle,
+    skybox::load_skybox,
+};
+
+pub mod bot;
+pub mod damage_popup;
+pub mod death_screen;
+pub mod finisher_prompt;
+pub mod health_bar;
+pub mod message;
+pub mod player;
+pub mod rng;
+pub mod weapon;
+
+// Health bars are shown at this height above bots.
+const HEALTH_BAR_HEIGHT: f32 = 0.6;
+
+// Max amount of bots alive at the same time, it keeps performance bounded.
+const MAX_ALIVE_BOTS: usize = 8;
+
+// Time (in seconds) between bot spawns at the beginning, it decreases as the time goes by until
+// it reaches the minimum.
+const INITIAL_SPAWN_INTERVAL: f32 = 10.0;
+const MIN_SPAWN_INTERVAL: f32 = 2.0;
+// How fast (in seconds per second) the spawn interval decreases.
+const SPAWN_INTERVAL_DECREASE_RATE: f32 = 0.05;
+
+struct SpawnPoint {
+    position: Vector3<f32>,
+    // Time (in seconds) left until the point could be used again.
+    cooldown: f32,
+}
+
+impl SpawnPoint {
+    fn new(position: Vector3<f32>) -> Self {
+        Self {
+            position,
+            cooldown: 0.0,
+        }
+    }
+}
+
+// Checks whether the game is paused, scripts must do nothing while it is.
+pub fn is_game_paused(plugins: &[Box<dyn Plugin>]) -> bool {
+    plugins
+        .iter()
+        .filter_map(|plugin| plugin.cast::<Game>())
+        .any(|game| game.paused)
+}
+
+// Checks whether the debug free camera is flying, the player must not touch the camera and must
+// not move or shoot while it is.
+pub fn is_free_camera_active(plugins: &[Box<dyn Plugin>]) -> bool {
+    plugins
+        .iter()
+        .filter_map(|plugin| plugin.cast::<Game>())
+        .any(|game| game.free_camera.is_active())
+}
+
+pub struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {
+    fn register(&self, context: PluginRegistrationContext) {
+        // Register scripts, so they could be saved with the scene and assigned in the editor.
+        let script_constructors = &context.serialization_context.script_constructors;
+        script_constructors.add::<Player>("Player");
+        script_constructors.add::<Weapon>("Weapon");
+        script_constructors.add::<Bot>("Bot");
+    }
+
+    fn create_instance(
+        &self,
+        override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {
+        Box::new(Game::new(override_scene, context))
+    }
+}
+
+fn create_player(scene: &mut Scene, resource_manager: ResourceManager) -> Handle<Node> {
+    // Weapon will be attached to this pivot.
+    let weapon_pivot = PivotBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(-0.1, -0.05, 0.015))
+                .build(),
+        ),
+    )
+    .build(&mut scene.graph);
+
+    // Move the camera a bit up to "emulate" head.
+    let camera = CameraBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.25, 0.0))
+                    .build(),
+            )
+            .with_children(&[weapon_pivot]),
+    )
+    .build(&mut scene.graph);
+
+    // The camera works fine without a skybox, so the game can go on if it can't be loaded.
+    let skybox = block_on(load_skybox(
+        resource_manager.clone(),
+        "data/textures/skybox",
+    ))
+    .map_err(Log::err)
+    .ok();
+    scene.graph[camera].as_camera_mut().set_skybox(skybox);
+
+    // Add capsule collider for the rigid body.
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::capsule_y(0.25, 0.2))
+        .build(&mut scene.graph);
+
+    // Create the weapon and "attach" it to the weapon pivot of the player.
+    let weapon = block_on(create_weapon(scene, resource_manager, collider));
+    scene.graph.link_nodes(weapon, weapon_pivot);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    // Offset player a bit.
+                    .with_local_position(Vector3::new(0.0, 1.0, -1.0))
+                    .build(),
+            )
+            .with_children(&[camera, collider])
+            // The script will drive the body using the input from the OS.
+            .with_script(Script::new(Player {
+                camera,
+                collider,
+                weapon,
+                ..Default::default()
+            })),
+    )
+    // We don't want the player to tilt.
+    .with_locked_rotations(true)
+    // We don't want the rigid body to sleep (be excluded from simulation)
+    .with_can_sleep(false)
+    .build(&mut scene.graph)
+}
+
+// UI elements over the scene are shown from the point of view of the player.
+fn player_camera(scene: &Scene) -> Option<Handle<Node>> {
+    scene
+        .graph
+        .linear_iter()
+        .find_map(|node| node.try_get_script::<Player>())
+        .map(|player| player.camera)
+}
+
+fn load_scene(context: &PluginContext) -> AsyncSceneLoader {
+    // Load the scene in the background, it will be added to the engine once loaded.
+    // Navigational mesh for the bots is made in the editor and saved along with the
+    // scene, so it will be loaded too.
+    AsyncSceneLoader::begin_loading(
+        asset_path("data/models/scene.rgs"),
+        context.serialization_context.clone(),
+        context.resource_manager.clone(),
+    )
+}
+
+pub struct Game {
+    scene: Handle<Scene>,
+    loader: Option<AsyncSceneLoader>,
+    paused: bool,
+    fps_counter: FpsCounter,
+    fullscreen: FullscreenToggle,
+    // Debug camera, it keeps the transform the player's camera had before it was taken.
+    free_camera: FreeCamera,
+    damage_popups: Vec<DamagePopup>,
+    death_screen: DeathScreen,
+    finisher_prompt: FinisherPrompt,
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    // Every random decision of the game (where bots appear, how bullet impacts look, etc.) is
+    // made by this generator, so the same seed gives the same gameplay. The seed could be
+    // changed at any time, it is used when the game is restarted.
+    pub seed: u64,
+    rng: XorShiftRng,
+    // Bot spawner state.
+    spawn_points: Vec<SpawnPoint>,
+    spawn_timer: f32,
+    // Time (in seconds) a spawn point rests after a bot has appeared at it, so bots won't keep
+    // appearing at the same place.
+    pub spawn_point_cooldown: f32,
+    // A place where hurt bots regroup, it is the spawn point that is the farthest from the
+    // player.
+    rally_point: Option<Vector3<f32>>,
+    // Total time of the game (in seconds), it is used to increase difficulty.
+    elapsed_time: f32,
+}
+
+impl Game {
+    pub fn new(override_scene: Handle<Scene>, context: PluginContext) -> Self {
+        let mut loader = None;
+        let scene = if override_scene.is_some() {
+            // The editor passes its own scene when the game is started from it.
+            override_scene
+        } else {
+            loader = Some(load_scene(&context));
+            Default::default()
+        };
+
+        let seed = initial_seed();
+
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            scene,
+            loader,
+            paused: false,
+            fps_counter: FpsCounter::new(context.user_interface),
+            fullscreen: Default::default(),
+            free_camera: Default::default(),
+            damage_popups: Default::default(),
+            death_screen: DeathScreen::new(context.user_interface),
+            finisher_prompt: FinisherPrompt::new(context.user_interface),
+            sender,
+            receiver,
+            seed,
+            rng: XorShiftRng::new(seed),
+            // Bots will appear at these points over time.
+            spawn_points: vec![
+                SpawnPoint::new(Vector3::new(-1.0, 1.0, 1.5)),
+                SpawnPoint::new(Vector3::new(-2.5, 1.0, 3.0)),
+                SpawnPoint::new(Vector3::new(1.5, 1.0, 3.0)),
+            ],
+            spawn_timer: INITIAL_SPAWN_INTERVAL,
+            spawn_point_cooldown: 6.0,
+            rally_point: None,
+            elapsed_time: 0.0,
+        }
+    }
+
+    // Starts the game from scratch by loading the scene again, everything related to the
+    // current scene is removed.
+    fn restart(&mut self, context: &mut PluginContext) {
+        let ui = &mut *context.user_interface;
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            // Health bars are not part of the scene, so they must be removed explicitly.
+            for node in scene.graph.linear_iter_mut() {
+                if let Some(bot) = node.try_get_script_mut::<Bot>() {
+                    if bot.health_bar.is_some() {
+                        bot.health_bar.remove(ui);
+                    }
+                }
+            }
+
+            context.scenes.remove(self.scene);
+        }
+        self.scene = Handle::NONE;
+
+        // The camera was removed along with the scene, the new player has its own.
+        self.free_camera = Default::default();
+
+        for popup in self.damage_popups.drain(..) {
+            popup.remove(ui);
+        }
+
+        self.death_screen.set_visibility(ui, false);
+        self.finisher_prompt.set_visibility(ui, false);
+
+        // Messages from the old scene make no sense for the new one.
+        while self.receiver.try_recv().is_ok() {}
+
+        // The new scene is not paused, its physics is enabled.
+        self.paused = false;
+
+        self.rng = XorShiftRng::new(self.seed);
+        self.spawn_timer = INITIAL_SPAWN_INTERVAL;
+        for spawn_point in self.spawn_points.iter_mut() {
+            spawn_point.cooldown = 0.0;
+        }
+        self.elapsed_time = 0.0;
+        self.rally_point = None;
+
+        self.loader = Some(load_scene(context));
+    }
+
+    fn update_spawner(&mut self, context: &mut PluginContext) {
+        self.elapsed_time += context.dt;
+
+        for spawn_point in self.spawn_points.iter_mut() {
+            spawn_point.cooldown = (spawn_point.cooldown - context.dt).max(0.0);
+        }
+
+        self.spawn_timer -= context.dt;
+        if self.spawn_timer > 0.0 {
+            return;
+        }
+
+        let available = self
+            .spawn_points
+            .iter()
+            .enumerate()
+            .filter(|(_, spawn_point)| spawn_point.cooldown <= 0.0)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        // If every point rests, wait for the first one to be ready. The timer stays expired, so
+        // the bot will appear right at that moment.
+        if available.is_empty() {
+            return;
+        }
+
+        // Bots appear more and more often as the time goes by.
+        self.spawn_timer = (INITIAL_SPAWN_INTERVAL
+            - self.elapsed_time * SPAWN_INTERVAL_DECREASE_RATE)
+            .max(MIN_SPAWN_INTERVAL);
+
+        let scene = &mut context.scenes[self.scene];
+
+        // Corpses stay in the scene for a while, they must not take the places of living bots.
+        let alive_bots = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.try_get_script::<Bot>())
+            .filter(|bot| !bot.is_dead())
+            .count();
+
+        if alive_bots >= MAX_ALIVE_BOTS {
+            return;
+        }
+
+        // Pick a random spawn point among the ones that are not resting.
+        let index = available[self.rng.gen_index(available.len())];
+        self.spawn_points[index].cooldown = self.spawn_point_cooldown;
+
+        // New bot patrols through all the spawn points, starting from its own.
+        let mut waypoints = self
+            .spawn_points
+            .iter()
+            .map(|spawn_point| spawn_point.position)
+            .collect::<Vec<_>>();
+        waypoints.rotate_left(index);
+
+        // Bot creation is asynchronous, but all its resources were already loaded when the
+        // first bot was created, so this won't block for long.
+        block_on(create_bot(
+            scene,
+            self.spawn_points[index].position,
+            waypoints,
+            context.resource_manager.clone(),
+        ));
+    }
+
+    // Removes dead bots once they've lain long enough, so the scene won't grow forever. Model
+    // of a bot is attached to its rigid body, so it is removed too.
+    fn remove_corpses(&mut self, context: &mut PluginContext) {
+        let scene = &mut context.scenes[self.scene];
+        let ui = &mut *context.user_interface;
+
+        let corpses = scene
+            .graph
+            .pair_iter()
+            .filter(|(_, node)| {
+                node.try_get_script::<Bot>()
+                    .map_or(false, |bot| bot.can_be_removed(scene))
+            })
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        for handle in corpses {
+            if let Some(bot) = scene.graph[handle].try_get_script_mut::<Bot>() {
+                if bot.health_bar.is_some() {
+                    bot.health_bar.remove(ui);
+                }
+            }
+
+            scene.graph.remove_node(handle);
+        }
+    }
+
+    pub fn rally_point(&self) -> Option<Vector3<f32>> {
+        self.rally_point
+    }
+
+    fn update_rally_point(&mut self, context: &PluginContext) {
+        let scene = &context.scenes[self.scene];
+
+        let player_position = scene
+            .graph
+            .linear_iter()
+            .find(|node| node.try_get_script::<Player>().is_some())
+            .map(|node| node.global_position());
+
+        self.rally_point = player_position.and_then(|player_position| {
+            self.spawn_points
+                .iter()
+                .map(|spawn_point| spawn_point.position)
+                .max_by(|a, b| {
+                    a.metric_distance(&player_position)
+                        .total_cmp(&b.metric_distance(&player_position))
+                })
+        });
+    }
+
+    fn update_health_bars(&mut self, context: &mut PluginContext) {
+        let scene = &mut context.scenes[self.scene];
+        let ui = &mut *context.user_interface;
+
+        let camera = match player_camera(scene) {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        let bots = scene
+            .graph
+            .pair_iter()
+            .filter(|(_, node)| node.try_get_script::<Bot>().is_some())
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        for handle in bots {
+            // `project` maps a point in the world to the screen, it returns `None` for points
+            // behind the camera.
+            let position = scene.graph[camera].as_camera().project(
+                scene.graph[handle].global_position() + Vector3::new(0.0, HEALTH_BAR_HEIGHT, 0.0),
+                ui.screen_size(),
+            );
+
+            let bot = scene.graph[handle].try_get_script_mut::<Bot>().unwrap();
+
+            // Dead bots don't need health bars, the bar is removed before the bot itself, so
+            // it won't stay on the screen forever.
+            if bot.is_dead() {
+                if bot.health_bar.is_some() {
+                    bot.health_bar.remove(ui);
+                }
+                continue;
+            }
+
+            if !bot.health_bar.is_some() {
+                bot.health_bar = HealthBar::new(ui);
+            }
+
+            bot.health_bar.update(ui, position, bot.health_ratio());
+        }
+    }
+
+    // Scripts keep a copy of the sender to send messages to the game.
+    pub fn message_sender(&self) -> Sender<Message> {
+        self.sender.clone()
+    }
+
+    fn handle_messages(&mut self, context: &mut PluginContext) {
+        let scene = &mut context.scenes[self.scene];
+
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                Message::DamagePlayer { amount } => {
+                    if let Some(player) = scene
+                        .graph
+                        .linear_iter_mut()
+                        .find_map(|node| node.try_get_script_mut::<Player>())
+                    {
+                        player.damage(amount);
+                    }
+                }
+            }
+        }
+    }
+
+    // Random number generator of the game, scripts must use it instead of their own.
+    pub fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+
+    // Shows how much damage was done at the given point of the world.
+    pub fn add_damage_popup(&mut self, position: Vector3<f32>, amount: f32) {
+        self.damage_popups.push(DamagePopup::new(position, amount));
+    }
+
+    fn update_damage_popups(&mut self, context: &mut PluginContext) {
+        let scene = &context.scenes[self.scene];
+        let ui = &mut *context.user_interface;
+
+        let camera = player_camera(scene);
+
+        // Popups are frozen while the game is paused.
+        let dt = if self.paused { 0.0 } else { context.dt };
+
+        for popup in self.damage_popups.iter_mut() {
+            let screen_position = camera.and_then(|camera| {
+                scene.graph[camera]
+                    .as_camera()
+                    .project(popup.position(), ui.screen_size())
+            });
+
+            popup.update(ui, dt, screen_position);
+        }
+
+        self.damage_popups.retain(|popup| {
+            if popup.is_expired() {
+                popup.remove(ui);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+        self.fps_counter.update(context.user_interface);
+
+        if let Some(loader) = self.loader.as_ref() {
+            if let Some(result) = loader.fetch_result() {
+                match result {
+                    Ok(mut scene) => {
+                        create_player(&mut scene, context.resource_manager.clone());
+
+                        // Add a bot with its patrol route.
+                        block_on(create_bot(
+                            &mut scene,
+                            Vector3::new(-1.0, 1.0, 1.5),
+                            vec![
+                                Vector3::new(-1.0, 1.0, 1.5),
+                                Vector3::new(-2.5, 1.0, 1.5),
+                                Vector3::new(-2.5, 1.0, 3.0),
+                                Vector3::new(-1.0, 1.0, 3.0),
+                            ],
+                            context.resource_manager.clone(),
+                        ));
+
+                        self.scene = context.scenes.add(scene);
+                    }
+                    Err(err) => Log::err(format!("Unable to load scene! Reason: {:?}", err)),
+                }
+
+                self.loader = None;
+            }
+        }
+
+        if context.scenes.try_get(self.scene).is_some() {
+            if !self.paused {
+                self.remove_corpses(context);
+                self.update_spawner(context);
+                self.update_rally_point(context);
+            }
+
+            self.free_camera
+                .update(&mut context.scenes[self.scene].graph, context.dt);
+
+            self.handle_messages(context);
+            self.update_health_bars(context);
+            self.update_damage_popups(context);
+
+            let player = context.scenes[self.scene]
+                .graph
+                .linear_iter()
+                .find_map(|node| node.try_get_script::<Player>());
+
+            // Dead player stays dead until the game is restarted from the death screen.
+            let is_player_dead = player.map_or(false, |player| player.is_dead());
+
+            let is_finisher_available =
+                player.map_or(false, |player| player.is_finisher_available());
+            self.finisher_prompt.set_visibility(
+                context.user_interface,
+                is_finisher_available && !self.paused && !self.free_camera.is_active(),
+            );
+
+            if is_player_dead && !self.death_screen.is_visible() {
+                self.death_screen
+                    .set_visibility(context.user_interface, true);
+            }
+        }
+    }
+
+    fn on_ui_message(
+        &mut self,
+        context: &mut PluginContext,
+        message: &UiMessage,
+        _control_flow: &mut ControlFlow,
+    ) {
+        if self.death_screen.is_restart_requested(message) {
+            self.restart(context);
+        }
+    }
+
+    fn on_os_event(
+        &mut self,
+        event: &Event<()>,
+        context: PluginContext,
+        control_flow: &mut ControlFlow,
+    ) {
+        self.fullscreen.process_os_event(event, context.window);
+        self.free_camera.process_os_event(event);
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if input.state == ElementState::Pressed => match input.virtual_keycode {
+                // Pause or unpause the game by hitting Escape. This event is handled even when
+                // the game is paused, otherwise there would be no way to unpause it.
+                Some(VirtualKeyCode::Escape) => {
+                    self.paused = !self.paused;
+
+                    // Physics must be frozen too, the scene is still rendered as usual.
+                    if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                        *scene.graph.physics.enabled = !self.paused;
+                    }
+                }
+                // Quit the game by hitting Q while it is paused, so a stray key press while
+                // playing won't close it. The free camera flies down with Q, so it must be
+                // returned to the player first.
+                Some(VirtualKeyCode::Q) if self.paused && !self.free_camera.is_active() => {
+                    *control_flow = ControlFlow::Exit
+                }
+                // Show or hide frame rate counter by hitting F3.
+                Some(VirtualKeyCode::F3) => self.fps_counter.toggle(context.user_interface),
+                // Let the camera fly around the scene by hitting F4, hitting it again returns
+                // the camera to the player.
+                Some(VirtualKeyCode::F4) => {
+                    if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                        let camera = player_camera(scene).unwrap_or_default();
+                        self.free_camera.toggle(&mut scene.graph, camera);
+                    }
+                }
+                _ => (),
+            },
+            Event::RedrawRequested(_) => self.fps_counter.register_frame(),
+            _ => (),
+        }
+    }
+}
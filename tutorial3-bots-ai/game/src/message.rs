@@ -0,0 +1,5 @@
+// Scripts can't change the state of the game directly, they send messages instead, and the
+// game handles them in its update.
+pub enum Message {
+    DamagePlayer { amount: f32 },
+}
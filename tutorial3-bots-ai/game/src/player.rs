@@ -0,0 +1,483 @@
+use crate::{bot::Bot, is_free_camera_active, is_game_paused, weapon::Weapon, Game};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    event::{DeviceEvent, ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent},
+    impl_component_provider,
+    scene::{
+        camera::Projection,
+        graph::Graph,
+        node::{Node, TypeUuidProvider},
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+use std::f32::consts::PI;
+
+// Time (in seconds) of the FOV punch, the FOV widens during the first fifth of it and then
+// recovers.
+const FOV_PUNCH_TIME: f32 = 0.35;
+const FOV_PUNCH_ATTACK: f32 = 0.2;
+
+// Time (in seconds) of the finisher, the bot dies at the end of it.
+const FINISHER_TIME: f32 = 0.6;
+// There're no animations of the player, so the finisher is a lunge of the camera: it moves
+// forward and dips down towards the bot, then returns. These are offsets (in meters) at the
+// peak of the lunge.
+const FINISHER_LUNGE_FORWARD: f32 = 0.3;
+const FINISHER_LUNGE_DOWN: f32 = 0.1;
+// The bot must be in front of the player, this is cosine of max angle between the direction the
+// player looks and the direction to the bot.
+const FINISHER_MIN_COS: f32 = 0.7;
+
+#[derive(Default, Debug, Clone)]
+struct InputController {
+    move_forward: bool,
+    move_backward: bool,
+    move_left: bool,
+    move_right: bool,
+    pitch: f32,
+    yaw: f32,
+    shoot: bool,
+    finish: bool,
+    // The finisher key was pressed since the last update, holding the key doesn't count.
+    finish_pressed: bool,
+}
+
+// The script must be assigned to a rigid body node, its camera and collider are children of
+// the body.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Player {
+    pub camera: Handle<Node>,
+    pub collider: Handle<Node>,
+    // A node with the Weapon script.
+    pub weapon: Handle<Node>,
+    pub health: f32,
+    // How many degrees the camera turns per pixel of mouse movement.
+    pub mouse_sensitivity: f32,
+    // Whether moving the mouse up makes the camera look down.
+    pub invert_y: bool,
+    // Whether the FOV of the camera briefly widens when the player takes damage.
+    pub fov_punch_enabled: bool,
+    // How many degrees the FOV widens by at the peak of the punch.
+    pub fov_punch_intensity: f32,
+    // Max distance (in meters) to a weak bot at which the player is able to finish it off.
+    pub finisher_range: f32,
+
+    // Input state is not needed in saved games and in the editor.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    controller: InputController,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fov_punch_timer: f32,
+    // The punch is added on top of the FOV of the camera, this is how much was added last
+    // frame. Only this amount is taken back, so anything else that changes the FOV is kept.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fov_punch_offset: f32,
+    // A weak bot in front of the player, the finisher is done on it.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    finisher_target: Handle<Node>,
+    // Time (in seconds) left until the end of the finisher, zero means there's no finisher.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    finisher_timer: f32,
+    // Health at the start of the finisher, any hit interrupts it.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    finisher_health: f32,
+    // Position of the camera before the lunge, it is restored when the finisher is over.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    finisher_camera_position: Vector3<f32>,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            collider: Default::default(),
+            weapon: Default::default(),
+            health: 100.0,
+            mouse_sensitivity: 0.5,
+            invert_y: false,
+            fov_punch_enabled: true,
+            fov_punch_intensity: 10.0,
+            finisher_range: 1.0,
+            controller: Default::default(),
+            fov_punch_timer: 0.0,
+            fov_punch_offset: 0.0,
+            finisher_target: Default::default(),
+            finisher_timer: 0.0,
+            finisher_health: 0.0,
+            finisher_camera_position: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(Player);
+
+impl TypeUuidProvider for Player {
+    fn type_uuid() -> Uuid {
+        uuid!("c5d1e8a4-7f3b-4a92-b6e0-1d84f2a97c35")
+    }
+}
+
+impl Player {
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    pub fn damage(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.health = (self.health - amount).max(0.0);
+
+            if self.fov_punch_enabled && amount > 0.0 {
+                self.fov_punch_timer = FOV_PUNCH_TIME;
+            }
+        }
+    }
+
+    // Whether there's a bot the player could finish off right now, the game shows a prompt then.
+    pub fn is_finisher_available(&self) -> bool {
+        !self.is_dead() && self.finisher_timer <= 0.0 && self.finisher_target.is_some()
+    }
+
+    fn is_finishing(&self) -> bool {
+        self.finisher_timer > 0.0
+    }
+
+    // Returns distance to the given bot, if the bot could be finished off by the player: it must
+    // be weak, close enough and in front of the player.
+    fn finisher_distance(
+        &self,
+        graph: &Graph,
+        self_handle: Handle<Node>,
+        bot: Handle<Node>,
+    ) -> Option<f32> {
+        let node = graph.try_get(bot)?;
+        if !node.try_get_script::<Bot>()?.can_be_finished() {
+            return None;
+        }
+
+        let offset = node.global_position() - graph[self_handle].global_position();
+        let direction = Vector3::new(offset.x, 0.0, offset.z);
+        let distance = direction.norm();
+
+        let in_front = direction
+            .try_normalize(f32::EPSILON)
+            .map_or(true, |direction| {
+                direction.dot(&graph[self_handle].look_vector()) >= FINISHER_MIN_COS
+            });
+
+        if distance <= self.finisher_range && in_front {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    fn find_finisher_target(&self, graph: &Graph, self_handle: Handle<Node>) -> Handle<Node> {
+        graph
+            .pair_iter()
+            .filter_map(|(handle, _)| {
+                Some((handle, self.finisher_distance(graph, self_handle, handle)?))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(Handle::NONE, |(handle, _)| handle)
+    }
+
+    fn begin_finisher(&mut self, graph: &mut Graph) {
+        if let Some(bot) = graph
+            .try_get_mut(self.finisher_target)
+            .and_then(|node| node.try_get_script_mut::<Bot>())
+        {
+            bot.set_stunned(true);
+
+            self.finisher_timer = FINISHER_TIME;
+            self.finisher_health = self.health;
+            self.finisher_camera_position = **graph[self.camera].local_transform().position();
+        }
+    }
+
+    // Stops the finisher, the bot is released if it is still alive.
+    fn end_finisher(&mut self, graph: &mut Graph) {
+        self.finisher_timer = 0.0;
+
+        if let Some(bot) = graph
+            .try_get_mut(self.finisher_target)
+            .and_then(|node| node.try_get_script_mut::<Bot>())
+        {
+            bot.set_stunned(false);
+        }
+
+        if let Some(camera) = graph.try_get_mut(self.camera) {
+            camera
+                .local_transform_mut()
+                .set_position(self.finisher_camera_position);
+        }
+    }
+
+    // Starts, plays and ends the finisher. Pressing the key again, being hit or losing the bot
+    // (it died or moved away) cancels the finisher. Returns `true` while the finisher is played,
+    // the player can't do anything else meanwhile.
+    fn update_finisher(&mut self, context: &mut ScriptContext, finish_pressed: bool) -> bool {
+        let graph = &mut context.scene.graph;
+
+        if !self.is_finishing() {
+            self.finisher_target = self.find_finisher_target(graph, context.handle);
+
+            if finish_pressed && self.finisher_target.is_some() {
+                self.begin_finisher(graph);
+            }
+
+            return self.is_finishing();
+        }
+
+        let is_target_valid = self
+            .finisher_distance(graph, context.handle, self.finisher_target)
+            .is_some();
+
+        if finish_pressed || self.health < self.finisher_health || !is_target_valid {
+            self.end_finisher(graph);
+            return false;
+        }
+
+        self.finisher_timer -= context.dt;
+
+        if self.finisher_timer > 0.0 {
+            // The lunge goes forward and back once during the finisher.
+            let k = (PI * (1.0 - self.finisher_timer / FINISHER_TIME)).sin();
+            graph[self.camera].local_transform_mut().set_position(
+                self.finisher_camera_position
+                    + Vector3::new(0.0, -FINISHER_LUNGE_DOWN * k, FINISHER_LUNGE_FORWARD * k),
+            );
+
+            return true;
+        }
+
+        // The finisher is done, kill the bot.
+        let finished = graph.try_get_mut(self.finisher_target).and_then(|node| {
+            let position = node.global_position();
+            Some((position, node.try_get_script_mut::<Bot>()?.finish()))
+        });
+
+        self.end_finisher(graph);
+        self.finisher_target = Handle::NONE;
+
+        if let Some((position, amount)) = finished {
+            if let Some(game) = context
+                .plugins
+                .iter_mut()
+                .find_map(|plugin| plugin.cast_mut::<Game>())
+            {
+                game.add_damage_popup(position, amount);
+            }
+        }
+
+        false
+    }
+
+    fn update_fov_punch(&mut self, graph: &mut Graph, dt: f32) {
+        if self.fov_punch_timer <= 0.0 && self.fov_punch_offset == 0.0 {
+            return;
+        }
+
+        self.fov_punch_timer = (self.fov_punch_timer - dt).max(0.0);
+
+        // Quickly widen, then slowly recover, the offset is exactly zero once the timer is over.
+        let t = 1.0 - self.fov_punch_timer / FOV_PUNCH_TIME;
+        let k = if self.fov_punch_timer <= 0.0 {
+            0.0
+        } else if t < FOV_PUNCH_ATTACK {
+            t / FOV_PUNCH_ATTACK
+        } else {
+            1.0 - (t - FOV_PUNCH_ATTACK) / (1.0 - FOV_PUNCH_ATTACK)
+        };
+        let offset = (k * self.fov_punch_intensity).to_radians();
+
+        if let Some(camera) = graph.try_get_mut(self.camera) {
+            let camera = camera.as_camera_mut();
+            if let Projection::Perspective(mut perspective) = camera.projection().clone() {
+                perspective.fov += offset - self.fov_punch_offset;
+                camera.set_projection(Projection::Perspective(perspective));
+            }
+        }
+
+        self.fov_punch_offset = offset;
+    }
+}
+
+impl ScriptTrait for Player {
+    fn on_os_event(&mut self, event: &Event<()>, context: &mut ScriptContext) {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { input, .. } => {
+                    if let Some(key_code) = input.virtual_keycode {
+                        match key_code {
+                            VirtualKeyCode::W => {
+                                self.controller.move_forward = input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::S => {
+                                self.controller.move_backward =
+                                    input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::A => {
+                                self.controller.move_left = input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::D => {
+                                self.controller.move_right = input.state == ElementState::Pressed;
+                            }
+                            VirtualKeyCode::F => {
+                                let pressed = input.state == ElementState::Pressed;
+                                // A held key sends the same event over and over, it must not
+                                // start and cancel the finisher all the time.
+                                if pressed && !self.controller.finish {
+                                    self.controller.finish_pressed = true;
+                                }
+                                self.controller.finish = pressed;
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                &WindowEvent::MouseInput { button, state, .. } => {
+                    if button == MouseButton::Left {
+                        self.controller.shoot = state == ElementState::Pressed;
+                    }
+                }
+                _ => {}
+            },
+            // Buttons are still tracked while paused or while the free camera flies, so
+            // releasing a button meanwhile won't leave it stuck, but the camera must stay where
+            // it is.
+            Event::DeviceEvent { event, .. }
+                if !is_game_paused(context.plugins) && !is_free_camera_active(context.plugins) =>
+            {
+                if let DeviceEvent::MouseMotion { delta } = event {
+                    let y_sign = if self.invert_y { -1.0 } else { 1.0 };
+
+                    self.controller.yaw -= self.mouse_sensitivity * delta.0 as f32;
+
+                    self.controller.pitch = (self.controller.pitch
+                        + y_sign * self.mouse_sensitivity * delta.1 as f32)
+                        .clamp(-90.0, 90.0);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if is_game_paused(context.plugins) {
+            return;
+        }
+
+        let scene = &mut context.scene;
+
+        self.update_fov_punch(&mut scene.graph, context.dt);
+
+        // The camera is taken by the free camera, the player just stands still and holds fire
+        // until it is returned.
+        if is_free_camera_active(context.plugins) {
+            if let Some(weapon) = scene.graph[self.weapon].try_get_script_mut::<Weapon>() {
+                weapon.trigger = false;
+            }
+
+            let body = scene.graph[context.handle].as_rigid_body_mut();
+            body.set_lin_vel(Vector3::new(0.0, body.lin_vel().y, 0.0));
+
+            return;
+        }
+
+        // Pull the trigger of the weapon while the shoot button is pressed, dead player can't
+        // shoot.
+        if let Some(weapon) = scene.graph[self.weapon].try_get_script_mut::<Weapon>() {
+            weapon.trigger = self.controller.shoot && !self.is_dead();
+        }
+
+        if self.is_dead() {
+            // Dead player can't move, the camera just lies on the floor until the game is
+            // restarted.
+            scene.graph[self.camera]
+                .local_transform_mut()
+                .set_position(Vector3::new(0.0, -0.2, 0.0))
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::z_axis(),
+                    80.0f32.to_radians(),
+                ));
+
+            let body = scene.graph[context.handle].as_rigid_body_mut();
+            body.set_lin_vel(Vector3::new(0.0, body.lin_vel().y, 0.0));
+
+            return;
+        }
+
+        let finish_pressed = std::mem::take(&mut self.controller.finish_pressed);
+        if self.update_finisher(context, finish_pressed) {
+            // The player stands still and holds fire during the finisher.
+            let scene = &mut context.scene;
+
+            if let Some(weapon) = scene.graph[self.weapon].try_get_script_mut::<Weapon>() {
+                weapon.trigger = false;
+            }
+
+            let body = scene.graph[context.handle].as_rigid_body_mut();
+            body.set_lin_vel(Vector3::new(0.0, body.lin_vel().y, 0.0));
+
+            return;
+        }
+
+        let scene = &mut context.scene;
+
+        // Set pitch for the camera. These lines responsible for up-down camera rotation.
+        scene.graph[self.camera].local_transform_mut().set_rotation(
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.controller.pitch.to_radians()),
+        );
+
+        // Borrow rigid body node, the script is assigned to it.
+        let body = scene.graph[context.handle].as_rigid_body_mut();
+
+        // Keep only vertical velocity, and drop horizontal.
+        let mut velocity = Vector3::new(0.0, body.lin_vel().y, 0.0);
+
+        // Change the velocity depending on the keys pressed.
+        if self.controller.move_forward {
+            // If we moving forward then add "look" vector of the body.
+            velocity += body.look_vector();
+        }
+        if self.controller.move_backward {
+            // If we moving backward then subtract "look" vector of the body.
+            velocity -= body.look_vector();
+        }
+        if self.controller.move_left {
+            // If we moving left then add "side" vector of the body.
+            velocity += body.side_vector();
+        }
+        if self.controller.move_right {
+            // If we moving right then subtract "side" vector of the body.
+            velocity -= body.side_vector();
+        }
+
+        // Finally new linear velocity.
+        body.set_lin_vel(velocity);
+
+        // Change the rotation of the rigid body according to current yaw. These lines responsible for
+        // left-right rotation.
+        body.local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                self.controller.yaw.to_radians(),
+            ));
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
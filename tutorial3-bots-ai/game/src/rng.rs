@@ -0,0 +1,98 @@
+use fyrox::utils::log::Log;
+use std::{
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Environment variable that sets the seed of the game, so the same gameplay could be reproduced.
+pub const SEED_VAR: &str = "TUTORIAL_SEED";
+
+// Small and fast random number generator (xorshift64*). Unlike the generator from the standard
+// library (or `rand`'s thread_rng), it is fully defined by its seed, so the same seed always
+// gives the same sequence of numbers.
+#[derive(Clone, Debug)]
+pub struct XorShiftRng {
+    state: u64,
+}
+
+impl XorShiftRng {
+    pub fn new(seed: u64) -> Self {
+        // Zero state is "stuck" - it gives zeros forever, so it is replaced with something else.
+        Self {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Returns a random index in [0; len) range, `len` must not be zero.
+    pub fn gen_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+// Returns the seed set by the environment variable, or a seed made from the current time. The
+// seed is logged, so a run could be reproduced later.
+pub fn initial_seed() -> u64 {
+    let seed = env::var(SEED_VAR)
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64)
+        });
+
+    Log::info(format!(
+        "Game seed is {}, set {} to reuse it.",
+        seed, SEED_VAR
+    ));
+
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = XorShiftRng::new(42);
+        let mut b = XorShiftRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_sequences() {
+        let mut a = XorShiftRng::new(1);
+        let mut b = XorShiftRng::new(2);
+        let a = (0..10).map(|_| a.next_u64()).collect::<Vec<_>>();
+        let b = (0..10).map(|_| b.next_u64()).collect::<Vec<_>>();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn zero_seed_is_not_stuck() {
+        let mut rng = XorShiftRng::new(0);
+        assert!((0..10).any(|_| rng.next_u64() != 0));
+    }
+
+    #[test]
+    fn index_is_in_range() {
+        let mut rng = XorShiftRng::new(7);
+        for len in 1..20 {
+            assert!(rng.gen_index(len) < len);
+        }
+    }
+}
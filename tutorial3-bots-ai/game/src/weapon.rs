@@ -0,0 +1,615 @@
+use crate::{bot::Bot, is_game_paused, Game};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        color::Color,
+        color_gradient::{ColorGradient, GradientPoint},
+        math::{ray::Ray, vector_to_quat, Vector3Ext},
+        pool::Handle,
+        reflect::prelude::*,
+        sstorage::ImmutableString,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_component_provider,
+    material::{Material, PropertyValue, SharedMaterial},
+    scene::{
+        base::BaseBuilder,
+        graph::{physics::RayCastOptions, Graph},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            MeshBuilder, RenderPath,
+        },
+        node::{Node, TypeUuidProvider},
+        particle_system::{
+            emitter::base::BaseEmitterBuilder, emitter::sphere::SphereEmitterBuilder,
+            ParticleSystemBuilder, ParticleSystemRng,
+        },
+        pivot::PivotBuilder,
+        rigidbody::RigidBody,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+    utils::log::Log,
+};
+use tutorial_common::assets::asset_path;
+
+// Kind of damage, it is tagged by whatever deals the damage: a weapon has its own damage type,
+// which is set in the editor or when the weapon is created. There're no explosives in this
+// tutorial, but anything that explodes must use `Explosion`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Visit, Reflect)]
+pub enum DamageType {
+    #[default]
+    Bullet,
+    Explosion,
+    Melee,
+}
+
+// Multipliers of incoming damage per damage type, values below one mean resistance, values above
+// one mean weakness. For example, an armored bot could have 0.5 for bullets and 2.0 for
+// explosions. Every multiplier is one by default, so the damage is not changed.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Resistances {
+    pub bullet: f32,
+    pub explosion: f32,
+    pub melee: f32,
+}
+
+impl Default for Resistances {
+    fn default() -> Self {
+        Self {
+            bullet: 1.0,
+            explosion: 1.0,
+            melee: 1.0,
+        }
+    }
+}
+
+impl Resistances {
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Bullet => self.bullet,
+            DamageType::Explosion => self.explosion,
+            DamageType::Melee => self.melee,
+        }
+    }
+}
+
+// The script must be assigned to the root node of a weapon model.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Weapon {
+    pub shot_point: Handle<Node>,
+    // Collider of the weapon's owner, shots must not hit it.
+    pub owner_collider: Handle<Node>,
+    pub damage: f32,
+    pub damage_type: DamageType,
+
+    // Whether the owner wants to shoot or not, it is set by the owner every frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub trigger: bool,
+
+    // Runtime state of the weapon is not needed in saved games and in the editor.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shot_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    recoil_offset: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    recoil_target_offset: Vector3<f32>,
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            shot_point: Default::default(),
+            owner_collider: Default::default(),
+            damage: 25.0,
+            damage_type: DamageType::Bullet,
+            trigger: false,
+            shot_timer: 0.0,
+            recoil_offset: Default::default(),
+            recoil_target_offset: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(Weapon);
+
+impl TypeUuidProvider for Weapon {
+    fn type_uuid() -> Uuid {
+        uuid!("a1c9d3f6-2b48-4e17-8f5a-6d0b93e4c27e")
+    }
+}
+
+pub async fn create_weapon(
+    scene: &mut Scene,
+    resource_manager: ResourceManager,
+    owner_collider: Handle<Node>,
+) -> Handle<Node> {
+    // Yeah, you need only few lines of code to load a model of any complexity. If the model
+    // can't be loaded, an empty node is used instead, so the game can go on without it.
+    let path = "data/models/m4.FBX";
+    let model = match resource_manager.request_model(asset_path(path)).await {
+        Ok(model) => model.instantiate(scene),
+        Err(err) => {
+            Log::err(format!(
+                "Unable to load weapon model {}! Reason: {:?}",
+                path, err
+            ));
+            PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph)
+        }
+    };
+
+    // A weapon without a shot point (like the empty one above) shoots from its origin.
+    let mut shot_point = scene.graph.find_by_name(model, "Weapon:ShotPoint");
+    if shot_point.is_none() {
+        shot_point = model;
+    }
+
+    scene.graph[model].set_script(Some(Script::new(Weapon {
+        shot_point,
+        owner_collider,
+        ..Default::default()
+    })));
+
+    model
+}
+
+fn create_bullet_impact(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    seed: u64,
+    pos: Vector3<f32>,
+    orientation: UnitQuaternion<f32>,
+) -> Handle<Node> {
+    // Create sphere emitter first.
+    let emitter = SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(200)
+            .with_spawn_rate(3000)
+            .with_size_modifier_range(-0.01..-0.0125)
+            .with_size_range(0.0075..0.015)
+            .with_lifetime_range(0.05..0.2)
+            .with_x_velocity_range(-0.0075..0.0075)
+            .with_y_velocity_range(-0.0075..0.0075)
+            .with_z_velocity_range(0.025..0.045)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.01)
+    .build();
+
+    // Color gradient will be used to modify color of each particle over its lifetime.
+    let color_gradient = {
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 0, 0)));
+        gradient.add_point(GradientPoint::new(0.05, Color::from_rgba(255, 160, 0, 255)));
+        gradient.add_point(GradientPoint::new(0.95, Color::from_rgba(255, 120, 0, 255)));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 60, 0, 0)));
+        gradient
+    };
+
+    // Create new transform to orient and position particle system.
+    let transform = TransformBuilder::new()
+        .with_local_position(pos)
+        .with_local_rotation(orientation)
+        .build();
+
+    // Finally create particle system with limited lifetime.
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_lifetime(1.0)
+            .with_local_transform(transform),
+    )
+    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+    .with_color_over_lifetime_gradient(color_gradient)
+    .with_emitters(vec![emitter])
+    // Particles are spawned with random size, speed, etc. The particle system has its own
+    // generator, its seed is taken from the game's one, so the same game seed gives the same
+    // particles.
+    .with_rng(ParticleSystemRng::new(seed))
+    // We'll use simple spark texture for each particle.
+    .with_texture(resource_manager.request_texture(asset_path("data/textures/spark.png")))
+    .build(graph)
+}
+
+fn create_shot_trail(
+    graph: &mut Graph,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    trail_length: f32,
+) {
+    let transform = TransformBuilder::new()
+        .with_local_position(origin)
+        // Scale the trail in XZ plane to make it thin, and apply `trail_length` scale on Y axis
+        // to stretch is out.
+        .with_local_scale(Vector3::new(0.0025, 0.0025, trail_length))
+        // Rotate the trail along given `direction`
+        .with_local_rotation(UnitQuaternion::face_towards(&direction, &Vector3::y()))
+        .build();
+
+    // Create unit cylinder with caps that faces toward Z axis.
+    let shape = SurfaceSharedData::new(SurfaceData::make_cylinder(
+        6,     // Count of sides
+        1.0,   // Radius
+        1.0,   // Height
+        false, // No caps are needed.
+        // Rotate vertical cylinder around X axis to make it face towards Z axis
+        &UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 90.0f32.to_radians()).to_homogeneous(),
+    ));
+
+    // Create an instance of standard material for the shot trail.
+    let mut material = Material::standard();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            // Set yellow-ish color.
+            PropertyValue::Color(Color::from_rgba(255, 255, 0, 120)),
+        )
+        .unwrap();
+
+    MeshBuilder::new(
+        BaseBuilder::new()
+            // Do not cast shadows.
+            .with_cast_shadows(false)
+            .with_local_transform(transform)
+            // Shot trail should live ~0.25 seconds, after that it will be automatically
+            // destroyed.
+            .with_lifetime(0.25),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(shape)
+        .with_material(SharedMaterial::new(material))
+        .build()])
+    // Make sure to set Forward render path, otherwise the object won't be
+    // transparent.
+    .with_render_path(RenderPath::Forward)
+    .build(graph);
+}
+
+impl Weapon {
+    pub fn can_shoot(&self) -> bool {
+        self.shot_timer <= 0.0
+    }
+
+    fn shoot(&mut self, context: &mut ScriptContext) {
+        self.shot_timer = 0.1;
+
+        self.recoil_target_offset = Vector3::new(0.0, 0.0, -0.025);
+
+        let scene = &mut context.scene;
+
+        let weapon_model = &scene.graph[context.handle];
+
+        // Make a ray that starts at the weapon's position in the world and look toward
+        // "look" vector of the weapon.
+        let ray = Ray::new(
+            scene.graph[self.shot_point].global_position(),
+            weapon_model.look_vector().scale(1000.0),
+        );
+
+        let mut intersections = Vec::new();
+
+        scene.graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(ray.origin),
+                max_len: ray.dir.norm(),
+                groups: Default::default(),
+                sort_results: true, // We need intersections to be sorted from closest to furthest.
+                ray_direction: ray.dir,
+            },
+            &mut intersections,
+        );
+
+        // Shots are loud, bots nearby will come to check what's going on.
+        for node in scene.graph.linear_iter_mut() {
+            let position = node.global_position();
+            if let Some(bot) = node.try_get_script_mut::<Bot>() {
+                bot.hear_noise(position, ray.origin);
+            }
+        }
+
+        // Ignore intersections with owner's capsule.
+        let trail_length = if let Some(intersection) = intersections
+            .into_iter()
+            .find(|i| i.collider != self.owner_collider)
+        {
+            let colliders_parent = scene.graph[intersection.collider].parent();
+
+            // If we've hit a bot, damage it.
+            if let Some(bot) = scene.graph[colliders_parent].try_get_script_mut::<Bot>() {
+                if !bot.is_dead() {
+                    let damage = bot.damage(self.damage, self.damage_type);
+
+                    // Let the game show how much damage was done.
+                    if let Some(game) = context
+                        .plugins
+                        .iter_mut()
+                        .find_map(|plugin| plugin.cast_mut::<Game>())
+                    {
+                        game.add_damage_popup(intersection.position.coords, damage);
+                    }
+                }
+            }
+
+            // Apply some force at the point of impact, unless the collider isn't attached to a
+            // rigid body.
+            if let Some(picked_rigid_body) = scene.graph[colliders_parent].cast_mut::<RigidBody>() {
+                picked_rigid_body.apply_force_at_point(
+                    ray.dir.normalize().scale(10.0),
+                    intersection.position.coords,
+                );
+                picked_rigid_body.wake_up();
+            }
+
+            // Add bullet impact effect.
+            let effect_orientation = vector_to_quat(intersection.normal);
+
+            let seed = context
+                .plugins
+                .iter_mut()
+                .find_map(|plugin| plugin.cast_mut::<Game>())
+                .map_or(0, |game| game.rng().next_u64());
+
+            create_bullet_impact(
+                &mut scene.graph,
+                context.resource_manager.clone(),
+                seed,
+                intersection.position.coords,
+                effect_orientation,
+            );
+
+            // Trail length will be the length of line between intersection point and ray origin.
+            (intersection.position.coords - ray.origin).norm()
+        } else {
+            // Otherwise trail length will be just the ray length.
+            ray.dir.norm()
+        };
+
+        create_shot_trail(&mut scene.graph, ray.origin, ray.dir, trail_length);
+    }
+}
+
+impl ScriptTrait for Weapon {
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if is_game_paused(context.plugins) {
+            return;
+        }
+
+        self.shot_timer = (self.shot_timer - context.dt).max(0.0);
+
+        // `follow` method defined in Vector3Ext trait and it just increases or
+        // decreases vector's value in order to "follow" the target value with
+        // given speed.
+        self.recoil_offset.follow(&self.recoil_target_offset, 0.5);
+
+        // Apply offset to weapon's model.
+        context.scene.graph[context.handle]
+            .local_transform_mut()
+            .set_position(self.recoil_offset);
+
+        // Check if we've reached target recoil offset.
+        if self
+            .recoil_offset
+            .metric_distance(&self.recoil_target_offset)
+            < 0.001
+        {
+            // And if so, reset offset to zero to return weapon at
+            // its default position.
+            self.recoil_target_offset = Default::default();
+        }
+
+        if self.trigger && self.can_shoot() {
+            self.shoot(context);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+// Melee attack that sweeps across an arc in front of the attacker. The sweep hits only during
+// the active window of the attack animation, anyone who is inside the arc at any moment of the
+// window is hit, so the only way to dodge is to leave the arc before the window starts.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct MeleeSweep {
+    pub damage: f32,
+    // Width of the arc (in degrees), it is centered on the direction the attacker faces.
+    pub arc: f32,
+    // How far (in meters) the sweep reaches.
+    pub range: f32,
+    // Active window (in seconds from the start of the attack animation), the sweep hits only
+    // between these moments.
+    pub active_start: f32,
+    pub active_end: f32,
+
+    // Time of the attack animation on previous frame, `None` when the attack has just started.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prev_time: Option<f32>,
+    // A swing hits only once, even if the target stays in the arc for the whole window.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    has_hit: bool,
+}
+
+impl Default for MeleeSweep {
+    fn default() -> Self {
+        Self {
+            damage: 10.0,
+            arc: 90.0,
+            range: 0.8,
+            active_start: 0.55,
+            active_end: 0.85,
+            prev_time: None,
+            has_hit: false,
+        }
+    }
+}
+
+impl MeleeSweep {
+    // Checks whether the target is inside the arc, the check is made in XZ plane, so height
+    // does not matter.
+    pub fn is_in_arc(
+        &self,
+        origin: Vector3<f32>,
+        forward: Vector3<f32>,
+        target: Vector3<f32>,
+    ) -> bool {
+        let offset = Vector3::new(target.x - origin.x, 0.0, target.z - origin.z);
+        let distance = offset.norm();
+        if distance > self.range {
+            return false;
+        }
+
+        let forward = Vector3::new(forward.x, 0.0, forward.z);
+        if distance <= f32::EPSILON || forward.norm() <= f32::EPSILON {
+            return true;
+        }
+
+        forward.angle(&offset).to_degrees() <= self.arc * 0.5
+    }
+
+    // Must be called every frame while the attack animation plays, `time` is its current time
+    // position. Returns `true` once per swing, when the target is in the arc during the active
+    // window.
+    pub fn update(&mut self, time: f32, target_in_arc: bool) -> bool {
+        let prev_time = match self.prev_time.replace(time) {
+            Some(prev_time) if prev_time <= time => prev_time,
+            // The animation is looped, it has started over with a new swing.
+            Some(_) => {
+                self.has_hit = false;
+                0.0
+            }
+            None => time,
+        };
+
+        // Frames could be long enough to skip the whole window, so the window is checked
+        // against the time passed since the previous frame, not just the current moment.
+        let is_active = prev_time <= self.active_end && time >= self.active_start;
+
+        if is_active && target_in_arc && !self.has_hit {
+            self.has_hit = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Must be called when the attack is interrupted, so the next one starts from scratch.
+    pub fn reset(&mut self) {
+        self.prev_time = None;
+        self.has_hit = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resistances_multipliers() {
+        // Damage is not changed by default.
+        let resistances = Resistances::default();
+        assert_eq!(resistances.multiplier(DamageType::Bullet), 1.0);
+        assert_eq!(resistances.multiplier(DamageType::Explosion), 1.0);
+        assert_eq!(resistances.multiplier(DamageType::Melee), 1.0);
+
+        // Each damage type has its own multiplier.
+        let resistances = Resistances {
+            bullet: 0.5,
+            explosion: 2.0,
+            melee: 0.0,
+        };
+        assert_eq!(resistances.multiplier(DamageType::Bullet), 0.5);
+        assert_eq!(resistances.multiplier(DamageType::Explosion), 2.0);
+        assert_eq!(resistances.multiplier(DamageType::Melee), 0.0);
+    }
+
+    #[test]
+    fn melee_sweep_arc() {
+        let sweep = MeleeSweep {
+            arc: 90.0,
+            range: 1.0,
+            ..Default::default()
+        };
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+        let forward = Vector3::new(0.0, 0.0, 1.0);
+
+        // Straight ahead, height does not matter.
+        assert!(sweep.is_in_arc(origin, forward, Vector3::new(0.0, 0.0, 0.5)));
+        assert!(sweep.is_in_arc(origin, forward, Vector3::new(0.0, 2.0, 0.5)));
+
+        // The arc is 90 degrees wide, so it reaches 45 degrees to each side.
+        let angle = 40.0f32.to_radians();
+        let inside = Vector3::new(angle.sin(), 0.0, angle.cos()).scale(0.5);
+        assert!(sweep.is_in_arc(origin, forward, inside));
+        let angle = 50.0f32.to_radians();
+        let outside = Vector3::new(-angle.sin(), 0.0, angle.cos()).scale(0.5);
+        assert!(!sweep.is_in_arc(origin, forward, outside));
+
+        // Behind the attacker and out of reach.
+        assert!(!sweep.is_in_arc(origin, forward, Vector3::new(0.0, 0.0, -0.5)));
+        assert!(!sweep.is_in_arc(origin, forward, Vector3::new(0.0, 0.0, 1.5)));
+    }
+
+    #[test]
+    fn melee_sweep_hits_only_in_active_window() {
+        let mut sweep = MeleeSweep {
+            active_start: 0.5,
+            active_end: 0.8,
+            ..Default::default()
+        };
+
+        // Nothing happens before the window, even with the target in the arc.
+        assert!(!sweep.update(0.1, true));
+        assert!(!sweep.update(0.4, true));
+
+        // The target has dodged while the window is open.
+        assert!(!sweep.update(0.6, false));
+
+        // The target is back in the arc, the swing hits, but only once.
+        assert!(sweep.update(0.7, true));
+        assert!(!sweep.update(0.75, true));
+
+        // A target that comes into the arc after the window is not hit.
+        sweep.reset();
+        assert!(!sweep.update(0.85, true));
+        assert!(!sweep.update(0.9, true));
+    }
+
+    #[test]
+    fn melee_sweep_long_frame_does_not_skip_window() {
+        let mut sweep = MeleeSweep {
+            active_start: 0.5,
+            active_end: 0.6,
+            ..Default::default()
+        };
+
+        assert!(!sweep.update(0.4, true));
+        // The whole window has passed between two frames.
+        assert!(sweep.update(0.7, true));
+    }
+
+    #[test]
+    fn melee_sweep_hits_again_on_next_swing() {
+        let mut sweep = MeleeSweep {
+            active_start: 0.5,
+            active_end: 0.8,
+            ..Default::default()
+        };
+
+        assert!(sweep.update(0.6, true));
+        // The animation has looped, this is a new swing.
+        assert!(!sweep.update(0.1, true));
+        assert!(sweep.update(0.6, true));
+
+        // An interrupted attack starts from scratch too.
+        sweep.reset();
+        assert!(sweep.update(0.6, true));
+    }
+}